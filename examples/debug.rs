@@ -92,5 +92,5 @@ fn new_from_sk<const MOD: &'static str>(sk: &SecretKey<MOD>) -> PublicKey<MOD> {
             }
         }
     }
-    return PublicKey { e };
+    PublicKey::new(e).unwrap()
 }
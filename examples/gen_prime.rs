@@ -0,0 +1,23 @@
+use num_bigint::BigInt;
+use octonion::utils::{gen_prime_congruent, gen_safe_prime};
+
+/// print a random prime as a line for src/consts.rs.
+/// usage: cargo run --example gen_prime -- <bits> [safe]
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let bits: u64 = args
+        .next()
+        .and_then(|b| b.parse().ok())
+        .expect("usage: gen_prime <bits> [safe]");
+    let safe = args.next().as_deref() == Some("safe");
+
+    let mut rng = rand::thread_rng();
+    let p = if safe {
+        gen_safe_prime(bits, &mut rng).unwrap()
+    } else {
+        // p = 3 mod 4 keeps sqrt_with_mod on its fast path
+        gen_prime_congruent(bits, &BigInt::from(3), &BigInt::from(4), &mut rng).unwrap()
+    };
+    println!("/// random {} bit prime", bits);
+    println!("pub const P{}_STR: &str = \"{}\";", bits, p);
+}
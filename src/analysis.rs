@@ -0,0 +1,172 @@
+//! known plaintext linearization attack.
+//!
+//! every ciphertext is C = pk(., mt), a linear image of its mediamtext, and decryption
+//! is linear in C. so an attacker who knows the public key and a few (PlainText, CipherText)
+//! pairs can set up a linear system over F_p and recover an equivalent decryption map
+//! without the secret key.
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+use num_bigint::RandBigInt;
+
+use crate::consts::{M2203_STR, M31_STR, M3217_STR, M521_STR, M9689_STR};
+use crate::crypto::cipher_text::CipherText;
+use crate::crypto::{PlainText, PublicKey, Schema};
+use crate::error::Result;
+use crate::linalg::{Matrix, Vector};
+
+pub mod exhaustive;
+#[cfg(test)]
+mod tests;
+
+/// number of fresh ciphertexts a recovered map has to decrypt before the attack counts as a success
+pub const VALIDATION_SAMPLES: usize = 8;
+
+/// linear functional p = <d, vec(C)>, where vec(C) is C flattened row major
+#[derive(Debug, Clone)]
+pub struct DecryptionMap<const MOD: &'static str> {
+    pub d: Vector<MOD>,
+}
+
+impl<const MOD: &'static str> DecryptionMap<MOD> {
+    pub fn decrypt(&self, ct: &CipherText<MOD>) -> PlainText<MOD> {
+        PlainText {
+            value: self.d.dot(&vectorize(ct)),
+        }
+    }
+}
+
+/// C as a vector of length 64, index 8 * ie + ix
+pub fn vectorize<const MOD: &'static str>(ct: &CipherText<MOD>) -> Vector<MOD> {
+    Vector::new(ct.to_matrix().into_vec())
+}
+
+/// try to recover a decryption map from known pairs.
+///
+/// with B = pk as a 64 x 8 matrix, every fresh ciphertext is vec(C) = B y for some y in F_p^8.
+/// each pair is projected to its coordinates y_i, and f in F_p^8 is solved from
+/// <f, y_i> = p_i. the map is then lifted back to F_p^64 by solving B^T d = f.
+/// returns None if some ciphertext is not in the image of B or the system is inconsistent.
+pub fn recover_decryption_map<const MOD: &'static str>(
+    pairs: &[(PlainText<MOD>, CipherText<MOD>)],
+    pk: &PublicKey<MOD>,
+) -> Result<Option<DecryptionMap<MOD>>> {
+    let b = pk.to_matrix();
+
+    let mut ys = Vec::with_capacity(pairs.len());
+    let mut ps = Vec::with_capacity(pairs.len());
+    for (pt, ct) in pairs {
+        match b.solve(&vectorize(ct))? {
+            Some(y) => ys.push(y.into_vec()),
+            None => return Ok(None),
+        }
+        ps.push(pt.value.clone());
+    }
+    let f = if ys.is_empty() {
+        Vector::zeros(8)
+    } else {
+        match Matrix::from_rows(ys).unwrap().solve(&Vector::new(ps))? {
+            Some(f) => f,
+            None => return Ok(None),
+        }
+    };
+
+    Ok(b.transpose().solve(&f)?.map(|d| DecryptionMap { d }))
+}
+
+/// rank of the known ciphertexts.
+/// the recovered map is unique on fresh ciphertexts once this stops growing.
+pub fn sample_rank<const MOD: &'static str>(
+    pairs: &[(PlainText<MOD>, CipherText<MOD>)],
+) -> Result<usize> {
+    if pairs.is_empty() {
+        return Ok(0);
+    }
+    let rows = pairs
+        .iter()
+        .map(|(_, ct)| vectorize(ct).into_vec())
+        .collect();
+    Matrix::<MOD>::from_rows(rows).unwrap().rank()
+}
+
+/// result of running the attack on fresh keys
+#[derive(Debug, Clone)]
+pub struct AttackReport {
+    pub modulus: &'static str,
+    pub trials: usize,
+    pub successes: usize,
+    /// known pairs needed by each successful trial
+    pub samples_required: Vec<usize>,
+}
+
+impl AttackReport {
+    pub fn success_rate(&self) -> f64 {
+        if self.trials == 0 {
+            return 0.0;
+        }
+        self.successes as f64 / self.trials as f64
+    }
+
+    /// most known pairs any successful trial needed
+    pub fn max_samples_required(&self) -> Option<usize> {
+        self.samples_required.iter().cloned().max()
+    }
+}
+
+/// generate fresh schema and keys `trials` times, feed known pairs one at a time
+/// and record how many were needed to decrypt VALIDATION_SAMPLES fresh ciphertexts.
+/// a trial fails if max_samples pairs are not enough.
+pub fn run_known_plaintext_attack<const MOD: &'static str>(
+    trials: usize,
+    max_samples: usize,
+) -> Result<AttackReport> {
+    let m = BigInt::from_str(MOD).unwrap();
+    let mut rng = rand::thread_rng();
+    let mut report = AttackReport {
+        modulus: MOD,
+        trials,
+        successes: 0,
+        samples_required: Vec::new(),
+    };
+
+    for _ in 0..trials {
+        let schema = Schema::<MOD>::new();
+        let (_, pk) = schema.gen_sk_pk();
+        let mut encrypt_random = || {
+            let pt = schema.new_plaintext(rng.gen_bigint_range(&BigInt::from(0), &m));
+            let ct = schema.encrypt(pt.clone(), &pk);
+            (pt, ct)
+        };
+
+        let validation: Vec<_> = (0..VALIDATION_SAMPLES).map(|_| encrypt_random()).collect();
+        let mut pairs = Vec::with_capacity(max_samples);
+        for n in 1..=max_samples {
+            pairs.push(encrypt_random());
+            let map = match recover_decryption_map(&pairs, &pk)? {
+                Some(map) => map,
+                None => continue,
+            };
+            if validation
+                .iter()
+                .all(|(pt, ct)| map.decrypt(ct).value == pt.value)
+            {
+                report.successes += 1;
+                report.samples_required.push(n);
+                break;
+            }
+        }
+    }
+    Ok(report)
+}
+
+/// run_known_plaintext_attack for every modulus in consts.
+/// key generation dominates, so large moduli take a long time.
+pub fn report_consts(trials: usize, max_samples: usize) -> Result<Vec<AttackReport>> {
+    Ok(vec![
+        run_known_plaintext_attack::<M31_STR>(trials, max_samples)?,
+        run_known_plaintext_attack::<M521_STR>(trials, max_samples)?,
+        run_known_plaintext_attack::<M2203_STR>(trials, max_samples)?,
+        run_known_plaintext_attack::<M3217_STR>(trials, max_samples)?,
+        run_known_plaintext_attack::<M9689_STR>(trials, max_samples)?,
+    ])
+}
@@ -0,0 +1,388 @@
+//! exhaustive analysis over all octonions for tiny moduli such as Octonion<"5">.
+//!
+//! there are p^8 octonions, so everything here is only usable for very small p.
+//! the (g, h) pairs grow like p^11 and have their own, tighter bound MAX_G_H_MODULUS.
+//! arithmetic is done on u64 coordinates and converted to Octonion<MOD> on output.
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+use crate::error::{OctonionError, Result};
+use crate::linalg::Matrix;
+use crate::types::Octonion;
+
+/// largest modulus accepted, 13^8 is already about 8 * 10^8 octonions
+pub const MAX_MODULUS: u64 = 13;
+
+/// largest modulus accepted by for_each_g_h and count_g_h, 5^11 is about 5 * 10^7 pairs
+pub const MAX_G_H_MODULUS: u64 = 5;
+
+/// MOD as u64, if it is small enough to enumerate
+pub fn small_modulus<const MOD: &'static str>() -> Result<u64> {
+    check_small_modulus::<MOD>(MAX_MODULUS)
+}
+
+fn check_small_modulus<const MOD: &'static str>(max: u64) -> Result<u64> {
+    let too_large = || OctonionError::InvalidModulus {
+        modulus: BigInt::from_str(MOD).unwrap(),
+        reason: format!("too large to enumerate, must be at most {}", max),
+    };
+    let p: u64 = MOD.parse().map_err(|_| too_large())?;
+    if p > max {
+        return Err(too_large());
+    }
+    if p < 3 || (2..p).any(|d| p.is_multiple_of(d)) {
+        return Err(OctonionError::InvalidModulus {
+            modulus: BigInt::from(p),
+            reason: "must be an odd prime".to_string(),
+        });
+    }
+    Ok(p)
+}
+
+fn to_octonion<const MOD: &'static str>(a: &[u64; 8]) -> Octonion<MOD> {
+    let mut o = Octonion::zero();
+    for i in 0..8 {
+        o[i] = BigInt::from(a[i]);
+    }
+    o
+}
+
+fn norm2(a: &[u64; 8], p: u64) -> u64 {
+    a.iter().map(|x| x * x % p).sum::<u64>() % p
+}
+
+fn dot(a: &[u64; 8], b: &[u64; 8], p: u64) -> u64 {
+    a.iter().zip(b.iter()).map(|(x, y)| x * y % p).sum::<u64>() % p
+}
+
+/// x^-1 mod p for prime p
+fn inverse_u64(x: u64, p: u64) -> u64 {
+    let mut ans = 1;
+    let mut base = x % p;
+    let mut e = p - 2;
+    while e > 0 {
+        if e & 1 == 1 {
+            ans = ans * base % p;
+        }
+        base = base * base % p;
+        e >>= 1;
+    }
+    ans
+}
+
+/// call f with every a in F_p^n, a[0] varies slowest
+fn for_each_coordinates<F: FnMut(&[u64; 8])>(p: u64, n: usize, mut f: F) {
+    let mut a = [0u64; 8];
+    loop {
+        f(&a);
+        // increment a[0..n] as a base p number, a[n - 1] is the lowest digit
+        let mut i = n;
+        loop {
+            if i == 0 {
+                return;
+            }
+            i -= 1;
+            a[i] += 1;
+            if a[i] < p {
+                break;
+            }
+            a[i] = 0;
+        }
+    }
+}
+
+/// call f with every octonion over F_MOD
+pub fn for_each_octonion<const MOD: &'static str, F: FnMut(Octonion<MOD>)>(mut f: F) -> Result<()> {
+    let p = small_modulus::<MOD>()?;
+    for_each_coordinates(p, 8, |a| f(to_octonion(a)));
+    Ok(())
+}
+
+/// order of the invertible element with real part a0 and norm n.
+///
+/// A^2 = 2 a0 A - n, so A^k = alpha_k + beta_k A and only (a0, n) matters.
+/// if A is real, A^k = a0^k.
+fn order_of(a0: u64, n: u64, is_real: bool, p: u64) -> u64 {
+    if is_real {
+        let mut x = a0;
+        let mut k = 1;
+        while x != 1 {
+            x = x * a0 % p;
+            k += 1;
+        }
+        return k;
+    }
+
+    let t = 2 * a0 % p;
+    // A^1 = 0 + 1 A
+    let (mut alpha, mut beta) = (0, 1);
+    let mut k = 1;
+    while !(alpha == 1 && beta == 0) {
+        // A^(k+1) = alpha A + beta (t A - n)
+        let next_alpha = (p - n * beta % p) % p;
+        let next_beta = (alpha + t * beta) % p;
+        alpha = next_alpha;
+        beta = next_beta;
+        k += 1;
+    }
+    k
+}
+
+/// smallest k > 0 with a^k = 1, None if a has no inverse
+pub fn order<const MOD: &'static str>(a: &Octonion<MOD>) -> Result<Option<u64>> {
+    let p = small_modulus::<MOD>()?;
+    if !a.has_inv() {
+        return Ok(None);
+    }
+    let coordinates = octonion_to_u64(a);
+    let is_real = coordinates[1..].iter().all(|x| *x == 0);
+    Ok(Some(order_of(
+        coordinates[0],
+        norm2(&coordinates, p),
+        is_real,
+        p,
+    )))
+}
+
+fn octonion_to_u64<const MOD: &'static str>(a: &Octonion<MOD>) -> [u64; 8] {
+    let mut coordinates = [0u64; 8];
+    for i in 0..8 {
+        coordinates[i] = a[i].to_string().parse().unwrap();
+    }
+    coordinates
+}
+
+/// every nonzero octonion with norm2() == 0
+pub fn zero_divisors<const MOD: &'static str>() -> Result<Vec<Octonion<MOD>>> {
+    let p = small_modulus::<MOD>()?;
+    let mut zero_divisors = Vec::new();
+    for_each_coordinates(p, 8, |a| {
+        if norm2(a, p) == 0 && a.iter().any(|x| *x != 0) {
+            zero_divisors.push(to_octonion(a));
+        }
+    });
+    Ok(zero_divisors)
+}
+
+/// order -> how many invertible elements have that order
+pub fn order_histogram<const MOD: &'static str>() -> Result<BTreeMap<u64, u64>> {
+    let p = small_modulus::<MOD>()?;
+    let mut memo = HashMap::new();
+    let mut histogram = BTreeMap::new();
+    for_each_coordinates(p, 8, |a| {
+        let n = norm2(a, p);
+        if n == 0 {
+            return;
+        }
+        let is_real = a[1..].iter().all(|x| *x == 0);
+        let order = *memo
+            .entry((a[0], n, is_real))
+            .or_insert_with(|| order_of(a[0], n, is_real, p));
+        *histogram.entry(order).or_insert(0) += 1;
+    });
+    Ok(histogram)
+}
+
+/// u64 coordinates of the kernel of h -> (h0, g . h), the h meeting the linear constraints
+fn h_basis<const MOD: &'static str>(g: &[u64; 8]) -> Result<Vec<[u64; 8]>> {
+    let mut data = vec![BigInt::from(0); 16];
+    data[0] = BigInt::from(1);
+    for i in 0..8 {
+        data[8 + i] = BigInt::from(g[i]);
+    }
+    let kernel = Matrix::<MOD>::new(2, 8, data)?.kernel()?;
+    Ok(kernel
+        .iter()
+        .map(|v| {
+            let mut b = [0u64; 8];
+            for i in 0..8 {
+                b[i] = v[i].to_u64().unwrap();
+            }
+            b
+        })
+        .collect())
+}
+
+/// sqrt[x] is some y with y^2 = x mod p, if there is one
+fn sqrt_table(p: u64) -> Vec<Option<u64>> {
+    let mut sqrt = vec![None; p as usize];
+    for y in 0..p {
+        sqrt[(y * y % p) as usize] = Some(y);
+    }
+    sqrt
+}
+
+/// call f with every nonzero h in the span of basis with |h|^2 = 0.
+/// the coefficient of the last basis vector solves a quadratic, so this takes p^(n - 1) steps
+fn for_each_isotropic<F: FnMut(&[u64; 8])>(
+    basis: &[[u64; 8]],
+    p: u64,
+    sqrt: &[Option<u64>],
+    mut f: F,
+) {
+    let (last, rest) = match basis.split_last() {
+        Some(split) => split,
+        None => return,
+    };
+    let a = norm2(last, p);
+    let a_inv = inverse_u64(a, p);
+    let mut ts = Vec::with_capacity(p as usize);
+    for_each_coordinates(p, rest.len(), |c| {
+        let mut u = [0u64; 8];
+        for (ck, b) in c.iter().zip(rest.iter()) {
+            for i in 0..8 {
+                u[i] = (u[i] + ck * b[i]) % p;
+            }
+        }
+        // |u + t last|^2 = a t^2 + 2 b t + c
+        let (b, c) = (dot(&u, last, p), norm2(&u, p));
+        ts.clear();
+        if a != 0 {
+            // t = (-b +- sqrt(b^2 - a c)) / a
+            let d = (b * b + p * p - a * c % p) % p;
+            if let Some(r) = sqrt[d as usize] {
+                ts.push((2 * p - b + r) % p * a_inv % p);
+                if r != 0 {
+                    ts.push((2 * p - b - r) % p * a_inv % p);
+                }
+            }
+        } else if b != 0 {
+            ts.push((p - c) % p * inverse_u64(2 * b % p, p) % p);
+        } else if c == 0 {
+            ts.extend(0..p);
+        }
+        for &t in ts.iter() {
+            let mut h = u;
+            for i in 0..8 {
+                h[i] = (h[i] + t * last[i]) % p;
+            }
+            if h.iter().any(|x| *x != 0) {
+                f(&h);
+            }
+        }
+    });
+}
+
+/// every (g, h) as u64 coordinates, g from the quadric and h from its kernel
+fn for_each_g_h_u64<const MOD: &'static str, F: FnMut(&[u64; 8], &[u64; 8])>(
+    mut f: F,
+) -> Result<()> {
+    let p = check_small_modulus::<MOD>(MAX_G_H_MODULUS)?;
+    let g0 = inverse_u64(2, p);
+    let sqrt = sqrt_table(p);
+
+    let mut gs = Vec::new();
+    for_each_coordinates(p, 7, |a| {
+        let mut g = [0u64; 8];
+        g[0] = g0;
+        g[1..].copy_from_slice(&a[..7]);
+        if norm2(&g, p) == 0 {
+            gs.push(g);
+        }
+    });
+
+    for g in gs.iter() {
+        // h0 = 0 and g . h = 0 are linear, only |h|^2 = 0 is left to solve
+        let basis = h_basis::<MOD>(g)?;
+        for_each_isotropic(&basis, p, &sqrt, |h| f(g, h));
+    }
+    Ok(())
+}
+
+/// every (g, h) with the constraints find_g_h solves:
+/// g0 = 1/2, h0 = 0, |g|^2 = 0, |h|^2 = 0, g1 h1 + ... + g7 h7 = 0 and h != 0.
+/// there are about p^11 of them, so MOD must be at most MAX_G_H_MODULUS
+pub fn for_each_g_h<const MOD: &'static str, F: FnMut(&Octonion<MOD>, &Octonion<MOD>)>(
+    mut f: F,
+) -> Result<()> {
+    for_each_g_h_u64::<MOD, _>(|g, h| f(&to_octonion(g), &to_octonion(h)))
+}
+
+/// number of valid (g, h) pairs
+pub fn count_g_h<const MOD: &'static str>() -> Result<u64> {
+    let mut count = 0;
+    for_each_g_h_u64::<MOD, _>(|_, _| count += 1)?;
+    Ok(count)
+}
+
+fn write_octonion<const MOD: &'static str, W: Write>(
+    w: &mut W,
+    a: &Octonion<MOD>,
+) -> io::Result<()> {
+    for i in 0..8 {
+        if i > 0 {
+            write!(w, ",")?;
+        }
+        write!(w, "{}", a[i])?;
+    }
+    Ok(())
+}
+
+fn to_io_error(e: OctonionError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, e.to_string())
+}
+
+/// csv with header a0,...,a7
+pub fn write_zero_divisors_csv<const MOD: &'static str, W: Write>(w: &mut W) -> io::Result<()> {
+    writeln!(w, "a0,a1,a2,a3,a4,a5,a6,a7")?;
+    for a in zero_divisors::<MOD>().map_err(to_io_error)? {
+        write_octonion(w, &a)?;
+        writeln!(w)?;
+    }
+    Ok(())
+}
+
+/// csv with header a0,...,a7,norm2,order for every invertible element
+pub fn write_orders_csv<const MOD: &'static str, W: Write>(w: &mut W) -> io::Result<()> {
+    let p = small_modulus::<MOD>().map_err(to_io_error)?;
+    let mut memo = HashMap::new();
+    writeln!(w, "a0,a1,a2,a3,a4,a5,a6,a7,norm2,order")?;
+
+    let mut result = Ok(());
+    for_each_coordinates(p, 8, |a| {
+        let n = norm2(a, p);
+        if n == 0 || result.is_err() {
+            return;
+        }
+        let is_real = a[1..].iter().all(|x| *x == 0);
+        let order = *memo
+            .entry((a[0], n, is_real))
+            .or_insert_with(|| order_of(a[0], n, is_real, p));
+        result =
+            write_octonion(w, &to_octonion::<MOD>(a)).and_then(|_| writeln!(w, ",{},{}", n, order));
+    });
+    result
+}
+
+/// csv with header order,count
+pub fn write_order_histogram_csv<const MOD: &'static str, W: Write>(w: &mut W) -> io::Result<()> {
+    writeln!(w, "order,count")?;
+    for (order, count) in order_histogram::<MOD>().map_err(to_io_error)? {
+        writeln!(w, "{},{}", order, count)?;
+    }
+    Ok(())
+}
+
+/// csv with header g0,...,g7,h0,...,h7
+pub fn write_g_h_csv<const MOD: &'static str, W: Write>(w: &mut W) -> io::Result<()> {
+    writeln!(w, "g0,g1,g2,g3,g4,g5,g6,g7,h0,h1,h2,h3,h4,h5,h6,h7")?;
+    let mut result = Ok(());
+    for_each_g_h::<MOD, _>(|g, h| {
+        if result.is_err() {
+            return;
+        }
+        result = write_octonion(w, g)
+            .and_then(|_| write!(w, ","))
+            .and_then(|_| write_octonion(w, h))
+            .and_then(|_| writeln!(w));
+    })
+    .map_err(to_io_error)?;
+    result
+}
+
+#[cfg(test)]
+mod tests;
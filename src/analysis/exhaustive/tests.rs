@@ -0,0 +1,77 @@
+use super::*;
+
+const P5: &str = "5";
+
+#[test]
+fn test_small_modulus() {
+    assert_eq!(small_modulus::<P5>().unwrap(), 5);
+    assert!(small_modulus::<"17">().is_err());
+    assert!(small_modulus::<"2">().is_err());
+    assert!(small_modulus::<"9">().is_err());
+    assert!(count_g_h::<"7">().is_err());
+}
+
+#[test]
+fn test_zero_divisors() {
+    let zero_divisors = zero_divisors::<P5>().unwrap();
+    // x0^2 + ... + x7^2 = 0 has p^7 + p^3 (p - 1) solutions over F_p
+    assert_eq!(zero_divisors.len(), 78125 + 500 - 1);
+    assert!(zero_divisors.iter().all(|a| !a.has_inv() && !a.is_zero()));
+}
+
+#[test]
+fn test_order() {
+    let histogram = order_histogram::<P5>().unwrap();
+    let invertible: u64 = histogram.values().sum();
+    assert_eq!(invertible + 78125 + 500, 5u64.pow(8));
+
+    let mut checked = 0;
+    for_each_octonion::<P5, _>(|a| {
+        // every 997th element is enough to cover all norms and real parts
+        checked += 1;
+        if checked % 997 != 0 {
+            return;
+        }
+        let order = match order(&a).unwrap() {
+            Some(order) => order,
+            None => return,
+        };
+        let mut power = a.clone();
+        for k in 1..order {
+            assert_ne!(power, Octonion::one(), "{} has order {} < {}", a, k, order);
+            power *= a.clone();
+        }
+        assert_eq!(power, Octonion::one());
+    })
+    .unwrap();
+}
+
+/// (p^6 + p^3) choices of g, each with p^5 + p^3 - p^2 - 1 choices of h
+fn expected_g_h(p: u64) -> u64 {
+    (p.pow(6) + p.pow(3)) * (p.pow(5) + p.pow(3) - p.pow(2) - 1)
+}
+
+#[test]
+fn test_g_h() {
+    let mut count = 0u64;
+    let mut pairs = std::collections::HashSet::new();
+    for_each_g_h::<"3", _>(|g, h| {
+        count += 1;
+        pairs.insert((g.clone(), h.clone()));
+        if !count.is_multiple_of(101) {
+            return;
+        }
+        assert_eq!(g.clone() * g.clone(), g.clone());
+        assert!((h.clone() * h.clone()).is_zero());
+        assert_eq!((BigInt::from(2) * &g.a0) % 3, BigInt::from(1));
+        assert_eq!((g.clone() * h.clone()).a0, BigInt::from(0));
+    })
+    .unwrap();
+    assert_eq!(count, expected_g_h(3));
+    assert_eq!(pairs.len() as u64, count);
+    assert_eq!(count_g_h::<P5>().unwrap(), expected_g_h(5));
+
+    let mut csv = Vec::new();
+    write_order_histogram_csv::<P5, _>(&mut csv).unwrap();
+    assert!(String::from_utf8(csv).unwrap().starts_with("order,count\n"));
+}
@@ -0,0 +1,58 @@
+use num_bigint::RandBigInt;
+use quickcheck_macros::quickcheck;
+
+use super::*;
+use crate::consts::M521_STR;
+
+const M: &str = M521_STR;
+
+#[quickcheck]
+fn test_recover_decryption_map(p: usize) -> bool {
+    let m = BigInt::from_str(M).unwrap();
+    let mut rng = rand::thread_rng();
+
+    let schema = Schema::<M>::new();
+    let (_, pk) = schema.gen_sk_pk();
+    let pairs: Vec<_> = (0..8)
+        .map(|_| {
+            let pt = schema.new_plaintext(rng.gen_bigint_range(&BigInt::from(0), &m));
+            let ct = schema.encrypt(pt.clone(), &pk);
+            (pt, ct)
+        })
+        .collect();
+
+    let map = match recover_decryption_map(&pairs, &pk).unwrap() {
+        Some(map) => map,
+        None => return false,
+    };
+    let ct = schema.encrypt(schema.new_plaintext(BigInt::from(p)), &pk);
+    map.decrypt(&ct).value == BigInt::from(p) % &m
+}
+
+#[test]
+fn test_sample_rank() {
+    let m = BigInt::from_str(M).unwrap();
+    let mut rng = rand::thread_rng();
+
+    let schema = Schema::<M>::new();
+    let (_, pk) = schema.gen_sk_pk();
+    let pairs: Vec<_> = (0..6)
+        .map(|_| {
+            let pt = schema.new_plaintext(rng.gen_bigint_range(&BigInt::from(0), &m));
+            let ct = schema.encrypt(pt.clone(), &pk);
+            (pt, ct)
+        })
+        .collect();
+    // mediamtexts live in span(g, h, gh, hg), and gh + hg = h for a verified schema
+    assert!(sample_rank(&pairs).unwrap() <= 3);
+}
+
+#[test]
+fn test_run_known_plaintext_attack() {
+    let report = run_known_plaintext_attack::<M31_STR>(2, 8).unwrap();
+    assert_eq!(report.trials, 2);
+    // the span has dimension 3, 8 random pairs fail to span it only with negligible probability
+    assert_eq!(report.successes, report.trials);
+    assert_eq!(report.success_rate(), 1.0);
+    assert!(report.max_samples_required().unwrap() <= 8);
+}
@@ -0,0 +1,374 @@
+//! arithmetic circuits over F_MOD, evaluated on plaintexts or ciphertexts.
+//!
+//! gates are hash consed, so building the same gate twice returns the same wire, and gates
+//! on constants are folded. operands always come before a gate, so the gate list is in
+//! topological order and evaluation is a single pass.
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+
+use crate::crypto::cipher_text::CipherText;
+use crate::crypto::PlainText;
+use crate::error::{OctonionError, Result};
+
+#[cfg(test)]
+mod tests;
+
+/// output of a gate
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Wire(usize);
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Gate {
+    /// i-th input
+    Input(usize),
+    Const(BigInt),
+    Add(Wire, Wire),
+    Sub(Wire, Wire),
+    Mul(Wire, Wire),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Circuit<const MOD: &'static str> {
+    gates: Vec<Gate>,
+    inputs: usize,
+    outputs: Vec<Wire>,
+    /// gate -> its wire, for common subexpression elimination
+    index: HashMap<Gate, Wire>,
+}
+
+/// value of a wire during encrypted evaluation, constants stay plain as long as possible.
+/// a ciphertext is 64 numbers, so it is boxed to keep constants small
+enum Value<const MOD: &'static str> {
+    Plain(BigInt),
+    Cipher(Box<CipherText<MOD>>),
+}
+
+impl<const MOD: &'static str> Value<MOD> {
+    fn cipher(ct: CipherText<MOD>) -> Self {
+        Value::Cipher(Box::new(ct))
+    }
+}
+
+impl<const MOD: &'static str> Default for Circuit<MOD> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const MOD: &'static str> Circuit<MOD> {
+    pub fn new() -> Self {
+        Self {
+            gates: Vec::new(),
+            inputs: 0,
+            outputs: Vec::new(),
+            index: HashMap::new(),
+        }
+    }
+
+    fn push(&mut self, gate: Gate) -> Wire {
+        if let Some(wire) = self.index.get(&gate) {
+            return *wire;
+        }
+        let wire = Wire(self.gates.len());
+        self.gates.push(gate.clone());
+        self.index.insert(gate, wire);
+        wire
+    }
+
+    fn constant_of(&self, w: Wire) -> Option<&BigInt> {
+        match &self.gates[w.0] {
+            Gate::Const(c) => Some(c),
+            _ => None,
+        }
+    }
+
+    fn check(&self, w: Wire) {
+        assert!(w.0 < self.gates.len(), "wire {} is not in the circuit", w.0);
+    }
+
+    /// a new input, inputs are numbered in the order they are created
+    pub fn input(&mut self) -> Wire {
+        let wire = self.push(Gate::Input(self.inputs));
+        self.inputs += 1;
+        wire
+    }
+
+    pub fn constant(&mut self, c: BigInt) -> Wire {
+        let m = BigInt::from_str(MOD).unwrap();
+        let mut c = c % &m;
+        if c < BigInt::from(0) {
+            c += &m;
+        }
+        self.push(Gate::Const(c))
+    }
+
+    pub fn add(&mut self, a: Wire, b: Wire) -> Wire {
+        self.check(a);
+        self.check(b);
+        let zero = BigInt::from(0);
+        match (self.constant_of(a), self.constant_of(b)) {
+            (Some(x), Some(y)) => {
+                let c = x + y;
+                self.constant(c)
+            }
+            (Some(x), None) if x == &zero => b,
+            (None, Some(y)) if y == &zero => a,
+            // commutative, so a + b and b + a share a gate
+            _ => self.push(Gate::Add(a.min(b), a.max(b))),
+        }
+    }
+
+    pub fn sub(&mut self, a: Wire, b: Wire) -> Wire {
+        self.check(a);
+        self.check(b);
+        if a == b {
+            return self.constant(BigInt::from(0));
+        }
+        match (self.constant_of(a), self.constant_of(b)) {
+            (Some(x), Some(y)) => {
+                let c = x - y;
+                self.constant(c)
+            }
+            (None, Some(y)) if y == &BigInt::from(0) => a,
+            _ => self.push(Gate::Sub(a, b)),
+        }
+    }
+
+    pub fn mul(&mut self, a: Wire, b: Wire) -> Wire {
+        self.check(a);
+        self.check(b);
+        let zero = BigInt::from(0);
+        let one = BigInt::from(1);
+        match (self.constant_of(a), self.constant_of(b)) {
+            (Some(x), Some(y)) => {
+                let c = x * y;
+                self.constant(c)
+            }
+            (Some(x), _) | (_, Some(x)) if x == &zero => self.constant(zero),
+            (Some(x), None) if x == &one => b,
+            (None, Some(y)) if y == &one => a,
+            _ => self.push(Gate::Mul(a.min(b), a.max(b))),
+        }
+    }
+
+    /// mark w as the next output
+    pub fn output(&mut self, w: Wire) {
+        self.check(w);
+        self.outputs.push(w);
+    }
+
+    pub fn gates(&self) -> &[Gate] {
+        &self.gates
+    }
+
+    pub fn inputs(&self) -> usize {
+        self.inputs
+    }
+
+    pub fn outputs(&self) -> &[Wire] {
+        &self.outputs
+    }
+
+    /// depth of ciphertext multiplications per wire, multiplying by a constant is free
+    fn depths(&self) -> Vec<usize> {
+        let mut depths: Vec<usize> = Vec::with_capacity(self.gates.len());
+        for gate in self.gates.iter() {
+            let depth = match gate {
+                Gate::Input(_) | Gate::Const(_) => 0,
+                Gate::Add(a, b) | Gate::Sub(a, b) => depths[a.0].max(depths[b.0]),
+                Gate::Mul(a, b) => {
+                    if self.constant_of(*a).is_some() || self.constant_of(*b).is_some() {
+                        depths[a.0].max(depths[b.0])
+                    } else {
+                        depths[a.0].max(depths[b.0]) + 1
+                    }
+                }
+            };
+            depths.push(depth);
+        }
+        depths
+    }
+
+    /// largest number of ciphertext multiplications on a path to an output
+    pub fn mul_depth(&self) -> usize {
+        let depths = self.depths();
+        self.outputs.iter().map(|w| depths[w.0]).max().unwrap_or(0)
+    }
+
+    fn check_inputs(&self, n: usize) -> Result<()> {
+        if n != self.inputs {
+            return Err(OctonionError::DimensionMismatch(format!(
+                "circuit has {} inputs, got {}",
+                self.inputs, n
+            )));
+        }
+        Ok(())
+    }
+
+    /// reference evaluation on plaintext values
+    pub fn eval_plain(&self, inputs: &[BigInt]) -> Result<Vec<BigInt>> {
+        self.check_inputs(inputs.len())?;
+        let m = BigInt::from_str(MOD).unwrap();
+        let mut values: Vec<BigInt> = Vec::with_capacity(self.gates.len());
+        for gate in self.gates.iter() {
+            let value = match gate {
+                Gate::Input(i) => &inputs[*i] % &m,
+                Gate::Const(c) => c.clone(),
+                Gate::Add(a, b) => (&values[a.0] + &values[b.0]) % &m,
+                Gate::Sub(a, b) => (&values[a.0] - &values[b.0]) % &m,
+                Gate::Mul(a, b) => (&values[a.0] * &values[b.0]) % &m,
+            };
+            let value = if value < BigInt::from(0) {
+                value + &m
+            } else {
+                value
+            };
+            values.push(value);
+        }
+        Ok(self.outputs.iter().map(|w| values[w.0].clone()).collect())
+    }
+
+    /// evaluation on ciphertexts with the CipherText operators.
+    /// unit is Schema::unit, constants are added with add_plain and multiplied with mul_scalar
+    pub fn eval_encrypted(
+        &self,
+        inputs: &[CipherText<MOD>],
+        unit: &CipherText<MOD>,
+    ) -> Result<Vec<CipherText<MOD>>> {
+        self.check_inputs(inputs.len())?;
+        let m = BigInt::from_str(MOD).unwrap();
+        let plain = |c: &BigInt| PlainText { value: c.clone() };
+        let mut values: Vec<Value<MOD>> = Vec::with_capacity(self.gates.len());
+        for gate in self.gates.iter() {
+            let value = match gate {
+                Gate::Input(i) => Value::cipher(inputs[*i].clone()),
+                Gate::Const(c) => Value::Plain(c.clone()),
+                Gate::Add(a, b) | Gate::Sub(a, b) | Gate::Mul(a, b) => {
+                    match (gate, &values[a.0], &values[b.0]) {
+                        (Gate::Add(..), Value::Cipher(x), Value::Cipher(y)) => {
+                            Value::cipher(&**x + &**y)
+                        }
+                        (Gate::Add(..), Value::Cipher(x), Value::Plain(c))
+                        | (Gate::Add(..), Value::Plain(c), Value::Cipher(x)) => {
+                            Value::cipher(x.add_plain(&plain(c), unit))
+                        }
+                        (Gate::Sub(..), Value::Cipher(x), Value::Cipher(y)) => {
+                            Value::cipher(&**x - &**y)
+                        }
+                        (Gate::Sub(..), Value::Cipher(x), Value::Plain(c)) => {
+                            Value::cipher(x.add_plain(&plain(&(&m - c)), unit))
+                        }
+                        (Gate::Sub(..), Value::Plain(c), Value::Cipher(x)) => {
+                            Value::cipher((-&**x).add_plain(&plain(c), unit))
+                        }
+                        (Gate::Mul(..), Value::Cipher(x), Value::Cipher(y)) => {
+                            Value::cipher(&**x * &**y)
+                        }
+                        (Gate::Mul(..), Value::Cipher(x), Value::Plain(c))
+                        | (Gate::Mul(..), Value::Plain(c), Value::Cipher(x)) => {
+                            Value::cipher(x.mul_scalar(c))
+                        }
+                        // constant folding leaves no gate on two constants
+                        _ => unreachable!("gate on two constants"),
+                    }
+                }
+            };
+            values.push(value);
+        }
+        Ok(self
+            .outputs
+            .iter()
+            .map(|w| match &values[w.0] {
+                Value::Cipher(ct) => (**ct).clone(),
+                Value::Plain(c) => unit.mul_scalar(c),
+            })
+            .collect())
+    }
+
+    /// one gate per line, `input`, `const c`, `add a b`, `sub a b` or `mul a b`
+    /// with a and b gate numbers, then `output w` per output
+    pub fn write<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        for gate in self.gates.iter() {
+            match gate {
+                Gate::Input(_) => writeln!(w, "input")?,
+                Gate::Const(c) => writeln!(w, "const {}", c)?,
+                Gate::Add(a, b) => writeln!(w, "add {} {}", a.0, b.0)?,
+                Gate::Sub(a, b) => writeln!(w, "sub {} {}", a.0, b.0)?,
+                Gate::Mul(a, b) => writeln!(w, "mul {} {}", a.0, b.0)?,
+            }
+        }
+        for output in self.outputs.iter() {
+            writeln!(w, "output {}", output.0)?;
+        }
+        Ok(())
+    }
+
+    /// read what write wrote. gates are rebuilt through the builder, so wire numbers
+    /// may change if the file was not written by write
+    pub fn read<R: BufRead>(r: R) -> io::Result<Self> {
+        let invalid = |line: usize, reason: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("line {}: {}", line + 1, reason),
+            )
+        };
+        let mut circuit = Self::new();
+        // gate number in the file -> wire
+        let mut wires: Vec<Wire> = Vec::new();
+        for (n, line) in r.lines().enumerate() {
+            let line = line?;
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let wire = |i: usize| -> io::Result<Wire> {
+                words
+                    .get(i)
+                    .and_then(|word| word.parse::<usize>().ok())
+                    .and_then(|i| wires.get(i).cloned())
+                    .ok_or_else(|| invalid(n, "bad wire"))
+            };
+            match words.first() {
+                None => continue,
+                Some(&"input") => wires.push(circuit.input()),
+                Some(&"const") => {
+                    let c = words
+                        .get(1)
+                        .and_then(|word| BigInt::from_str(word).ok())
+                        .ok_or_else(|| invalid(n, "bad constant"))?;
+                    wires.push(circuit.constant(c));
+                }
+                Some(&"add") => {
+                    let (a, b) = (wire(1)?, wire(2)?);
+                    wires.push(circuit.add(a, b));
+                }
+                Some(&"sub") => {
+                    let (a, b) = (wire(1)?, wire(2)?);
+                    wires.push(circuit.sub(a, b));
+                }
+                Some(&"mul") => {
+                    let (a, b) = (wire(1)?, wire(2)?);
+                    wires.push(circuit.mul(a, b));
+                }
+                Some(&"output") => {
+                    let w = wire(1)?;
+                    circuit.output(w);
+                }
+                Some(_) => return Err(invalid(n, "unknown gate")),
+            }
+        }
+        Ok(circuit)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut w = BufWriter::new(File::create(path)?);
+        self.write(&mut w)?;
+        w.flush()
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+}
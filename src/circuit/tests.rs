@@ -0,0 +1,163 @@
+use num_bigint::RandBigInt;
+use quickcheck_macros::quickcheck;
+
+use super::*;
+use crate::consts::M521_STR;
+use crate::crypto::Schema;
+
+const M: &str = M521_STR;
+
+/// outputs (x + y) * (x - y) + 3 and x^4 y + 5 x
+fn sample_circuit() -> Circuit<M> {
+    let mut c = Circuit::new();
+    let x = c.input();
+    let y = c.input();
+    let sum = c.add(x, y);
+    let diff = c.sub(x, y);
+    let prod = c.mul(sum, diff);
+    let three = c.constant(BigInt::from(3));
+    let out = c.add(prod, three);
+    c.output(out);
+
+    let x2 = c.mul(x, x);
+    let x4 = c.mul(x2, x2);
+    let x4y = c.mul(x4, y);
+    let five = c.constant(BigInt::from(5));
+    let x5 = c.mul(five, x);
+    let out = c.add(x4y, x5);
+    c.output(out);
+    c
+}
+
+fn random_element() -> BigInt {
+    let m = BigInt::from_str(M).unwrap();
+    rand::thread_rng().gen_bigint_range(&BigInt::from(0), &m)
+}
+
+#[quickcheck]
+fn test_eval_plain(x: i64, y: i64) -> bool {
+    let m = BigInt::from_str(M).unwrap();
+    let (x, y) = (BigInt::from(x), BigInt::from(y));
+    let expected = vec![(&x + &y) * (&x - &y) + 3, x.pow(4) * &y + 5 * &x];
+    let expected: Vec<BigInt> = expected.into_iter().map(|v| ((v % &m) + &m) % &m).collect();
+    sample_circuit().eval_plain(&[x, y]).unwrap() == expected
+}
+
+#[test]
+fn test_eval_encrypted() {
+    let schema = Schema::<M>::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let unit = schema.unit(&pk);
+    let circuit = sample_circuit();
+
+    let xs = vec![random_element(), random_element()];
+    let cts: Vec<_> = xs
+        .iter()
+        .map(|x| schema.encrypt(schema.new_plaintext(x.clone()), &pk))
+        .collect();
+    let decrypted: Vec<BigInt> = circuit
+        .eval_encrypted(&cts, &unit)
+        .unwrap()
+        .into_iter()
+        .map(|ct| schema.decrypt(ct, &sk).unwrap().value)
+        .collect();
+    assert_eq!(decrypted, circuit.eval_plain(&xs).unwrap());
+    assert!(circuit.eval_encrypted(&cts[..1], &unit).is_err());
+}
+
+#[test]
+fn test_constant_output() {
+    let schema = Schema::<M>::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let mut c = Circuit::<M>::new();
+    let x = c.input();
+    let two = c.constant(BigInt::from(2));
+    let seven = c.constant(BigInt::from(7));
+    let nine = c.add(two, seven);
+    let minus = c.sub(two, x);
+    c.output(nine);
+    c.output(minus);
+
+    let ct = schema.encrypt(schema.new_plaintext(BigInt::from(10)), &pk);
+    let outputs = c.eval_encrypted(&[ct], &schema.unit(&pk)).unwrap();
+    let decrypted: Vec<_> = outputs
+        .into_iter()
+        .map(|ct| schema.decrypt(ct, &sk).unwrap().value)
+        .collect();
+    let m = BigInt::from_str(M).unwrap();
+    assert_eq!(decrypted, vec![BigInt::from(9), m - 8]);
+}
+
+#[test]
+fn test_cse_and_folding() {
+    let mut c = Circuit::<M>::new();
+    let x = c.input();
+    let y = c.input();
+    let a = c.add(x, y);
+    let b = c.add(y, x);
+    assert_eq!(a, b);
+    assert_eq!(c.mul(x, y), c.mul(y, x));
+    assert_ne!(c.sub(x, y), c.sub(y, x));
+
+    let two = c.constant(BigInt::from(2));
+    let three = c.constant(BigInt::from(3));
+    let six = c.mul(two, three);
+    assert_eq!(c.gates()[six.0], Gate::Const(BigInt::from(6)));
+    assert_eq!(c.constant(BigInt::from(6)), six);
+
+    let zero = c.constant(BigInt::from(0));
+    let one = c.constant(BigInt::from(1));
+    assert_eq!(c.add(x, zero), x);
+    assert_eq!(c.mul(one, x), x);
+    assert_eq!(c.mul(x, zero), zero);
+    assert_eq!(c.sub(x, x), zero);
+    assert_eq!(c.constant(BigInt::from(-1)), c.sub(zero, one));
+    // x, y, x + y, x y, x - y, y - x, 2, 3, 6, 0, 1, -1
+    assert_eq!(c.gates().len(), 12);
+}
+
+#[test]
+fn test_mul_depth() {
+    let c = sample_circuit();
+    // x^4 y: x^2, x^4, x^4 y
+    assert_eq!(c.mul_depth(), 3);
+
+    let mut c = Circuit::<M>::new();
+    let x = c.input();
+    let five = c.constant(BigInt::from(5));
+    let y = c.mul(five, x);
+    c.output(y);
+    assert_eq!(c.mul_depth(), 0);
+    let z = c.mul(y, x);
+    c.output(z);
+    assert_eq!(c.mul_depth(), 1);
+}
+
+#[test]
+fn test_save_load() {
+    let c = sample_circuit();
+    let mut buf = Vec::new();
+    c.write(&mut buf).unwrap();
+    let loaded = Circuit::<M>::read(&buf[..]).unwrap();
+    assert_eq!(loaded, c);
+
+    let path = std::env::temp_dir().join(format!("octonion_circuit_{}.txt", std::process::id()));
+    c.save(&path).unwrap();
+    let loaded = Circuit::<M>::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, c);
+
+    for bad in [
+        "input\nadd 0 1\n",
+        "input\nfoo 0\n",
+        "const x\n",
+        "output 0\n",
+    ]
+    .iter()
+    {
+        assert_eq!(
+            Circuit::<M>::read(bad.as_bytes()).unwrap_err().kind(),
+            io::ErrorKind::InvalidData
+        );
+    }
+}
@@ -1,20 +1,31 @@
 use std::fmt::Formatter;
+use std::ops::Index;
 use std::{fmt::Display, str::FromStr};
 
 use num_bigint::BigInt;
 use num_bigint::RandBigInt;
 use rand;
+use rand::seq::SliceRandom;
+use rand::Rng;
 
 use crate::crypto::cipher_text::CipherText;
+use crate::error::{OctonionError, Result};
+use crate::linalg::{Matrix, Vector};
+use crate::polynomial::{self, Polynomial};
 use crate::types::Octonion;
-use crate::utils::gen_rand_octonion_which_has_inv;
+use crate::utils::check_modulus;
+use crate::utils::gen_rand_octonion_which_has_inv_with_rng;
 use crate::utils::inverse;
 use crate::utils::is_residue;
 use crate::utils::sqrt_with_mod;
 
+pub mod aggregate;
+pub mod boolean;
 pub mod cipher_text;
+pub mod eval;
 #[cfg(test)]
 mod tests;
+pub mod vector;
 
 /// octonion schema
 #[derive(Debug, Clone)]
@@ -23,6 +34,69 @@ pub struct Schema<const MOD: &'static str> {
     h: Octonion<MOD>,
 }
 
+/// why a random try of find_g_h was thrown away
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GHRejection {
+    /// -(g0^2 + ... + g6^2) has no square root
+    G7NotResidue,
+    /// the quadratic for h7 has no solution
+    DiscriminantNotResidue,
+    /// g6^2 + g7^2 = 0
+    DegenerateDenominator,
+    /// h6 is solved by dividing by g6
+    G6Zero,
+}
+
+/// tries of find_g_h_bounded, rejected ones by reason
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GHSearchStats {
+    pub attempts: usize,
+    pub g7_not_residue: usize,
+    pub discriminant_not_residue: usize,
+    pub degenerate_denominator: usize,
+    pub g6_zero: usize,
+}
+
+impl GHSearchStats {
+    fn record(&mut self, rejection: GHRejection) {
+        match rejection {
+            GHRejection::G7NotResidue => self.g7_not_residue += 1,
+            GHRejection::DiscriminantNotResidue => self.discriminant_not_residue += 1,
+            GHRejection::DegenerateDenominator => self.degenerate_denominator += 1,
+            GHRejection::G6Zero => self.g6_zero += 1,
+        }
+    }
+
+    pub fn rejected(&self) -> usize {
+        self.g7_not_residue
+            + self.discriminant_not_residue
+            + self.degenerate_denominator
+            + self.g6_zero
+    }
+}
+
+impl Display for GHSearchStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(
+            f,
+            "{} attempts, rejected: g7 not residue {}, discriminant not residue {}, degenerate denominator {}, g6 zero {}",
+            self.attempts,
+            self.g7_not_residue,
+            self.discriminant_not_residue,
+            self.degenerate_denominator,
+            self.g6_zero
+        )
+    }
+}
+
+/// g and both h found by find_g_h_bounded
+#[derive(Debug, Clone)]
+pub struct GHSolution<const MOD: &'static str> {
+    pub g: Octonion<MOD>,
+    pub hs: (Octonion<MOD>, Octonion<MOD>),
+    pub stats: GHSearchStats,
+}
+
 #[derive(Debug, Clone)]
 pub struct PlainText<const MOD: &'static str> {
     pub value: BigInt,
@@ -33,6 +107,31 @@ pub struct MediamText<const MOD: &'static str> {
     pub value: Octonion<MOD>,
 }
 
+impl<const MOD: &'static str> MediamText<MOD> {
+    /// (p, u, v) with self = p g + u h + v gh in the basis g, h, gh, None if self is not in
+    /// their span. hg = h - gh for a verified schema, so p g + u h + v gh + w hg
+    /// decomposes to (p, u + w, v - w)
+    pub fn decompose(&self, schema: &Schema<MOD>) -> Option<(BigInt, BigInt, BigInt)> {
+        let gh = schema.g.clone() * schema.h.clone();
+        let mut data = Vec::with_capacity(24);
+        for i in 0..8 {
+            data.extend(vec![
+                schema.g[i].clone(),
+                schema.h[i].clone(),
+                gh[i].clone(),
+            ]);
+        }
+        let basis = Matrix::<MOD>::new(8, 3, data).unwrap();
+        let x = basis.solve(&Vector::from(&self.value)).ok()??.into_vec();
+        Some((x[0].clone(), x[1].clone(), x[2].clone()))
+    }
+
+    /// is self of the form p g + u h + v gh
+    pub fn is_well_formed(&self, schema: &Schema<MOD>) -> bool {
+        self.decompose(schema).is_some()
+    }
+}
+
 impl<const MOD: &'static str> Display for MediamText<MOD> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
         write!(f, "{}", self.value)?;
@@ -48,6 +147,26 @@ pub struct SecretKey<const MOD: &'static str> {
     pub a: Vec<Octonion<MOD>>,
 }
 
+impl<const MOD: &'static str> SecretKey<MOD> {
+    /// A_1^-1, ..., A_h^-1, inverted in parallel with the parallel feature
+    fn inverses(&self) -> Result<Vec<Octonion<MOD>>> {
+        let inverse = |(i, a): (usize, &Octonion<MOD>)| {
+            a.inverse().ok_or_else(|| {
+                OctonionError::MalformedKey(format!("A_{} = {} has no inverse", i + 1, a))
+            })
+        };
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            self.a.par_iter().enumerate().map(inverse).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            self.a.iter().enumerate().map(inverse).collect()
+        }
+    }
+}
+
 impl<const MOD: &'static str> Display for SecretKey<MOD> {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
         write!(f, "h: {}, a:", self.h)?;
@@ -62,7 +181,7 @@ impl<const MOD: &'static str> Display for SecretKey<MOD> {
 pub struct PublicKey<const MOD: &'static str> {
     /// coefficients f: (O \times O) -> O
     /// ijk -> i x y ((e000 * x0 * y0 + ... + e077 * x7 * y7), ...)
-    pub e: Vec<Vec<Vec<BigInt>>>,
+    pub(crate) e: [[[BigInt; 8]; 8]; 8],
 }
 
 impl<const MOD: &'static str> Display for PublicKey<MOD> {
@@ -84,15 +203,99 @@ impl<const MOD: &'static str> Display for PublicKey<MOD> {
     }
 }
 
+impl<const MOD: &'static str> Index<(usize, usize, usize)> for PublicKey<MOD> {
+    type Output = BigInt;
+    fn index(&self, (ie, ix, iy): (usize, usize, usize)) -> &BigInt {
+        &self.e[ie][ix][iy]
+    }
+}
+
 impl<const MOD: &'static str> PublicKey<MOD> {
+    /// build a public key from raw coefficients.
+    /// e must be 8 x 8 x 8 and every coefficient must be in [0, MOD).
+    pub fn new(e: Vec<Vec<Vec<BigInt>>>) -> Result<Self> {
+        let m = BigInt::from_str(MOD).unwrap();
+        if e.len() != 8 {
+            return Err(OctonionError::MalformedKey(format!(
+                "public key must have 8 planes, got {}",
+                e.len()
+            )));
+        }
+
+        let mut coefficients: [[[BigInt; 8]; 8]; 8] = Default::default();
+        for (ie, plane) in e.into_iter().enumerate() {
+            if plane.len() != 8 {
+                return Err(OctonionError::MalformedKey(format!(
+                    "public key plane {} must have 8 rows, got {}",
+                    ie,
+                    plane.len()
+                )));
+            }
+            for (ix, row) in plane.into_iter().enumerate() {
+                if row.len() != 8 {
+                    return Err(OctonionError::MalformedKey(format!(
+                        "public key row e[{}][{}] must have 8 columns, got {}",
+                        ie,
+                        ix,
+                        row.len()
+                    )));
+                }
+                for (iy, c) in row.into_iter().enumerate() {
+                    if c < BigInt::from(0) || c >= m {
+                        return Err(OctonionError::ModulusMismatch {
+                            value: c,
+                            modulus: m,
+                        });
+                    }
+                    coefficients[ie][ix][iy] = c;
+                }
+            }
+        }
+        Ok(PublicKey { e: coefficients })
+    }
+
+    /// e[ie][ix][iy]
+    pub fn coefficients(&self) -> &[[[BigInt; 8]; 8]; 8] {
+        &self.e
+    }
+
+    /// 64 x 8 matrix, row 8 * ie + ix and column iy is e[ie][ix][iy]
+    pub fn to_matrix(&self) -> Matrix<MOD> {
+        let mut data = Vec::with_capacity(512);
+        for ie in 0..8 {
+            for ix in 0..8 {
+                data.extend(self.e[ie][ix].iter().cloned());
+            }
+        }
+        Matrix::new(64, 8, data).unwrap()
+    }
+
+    /// the ciphertext of mediamtext v, e[ie][ix] = sum_iy pk.e[ie][ix][iy] * v[iy].
+    /// works on e directly, every encryption goes through here
+    pub(crate) fn apply(&self, v: &Octonion<MOD>) -> CipherText<MOD> {
+        let m = BigInt::from_str(MOD).unwrap();
+        let mut e: [[BigInt; 8]; 8] = Default::default();
+        for ie in 0..8 {
+            for ix in 0..8 {
+                let mut c = BigInt::from(0);
+                for iy in 0..8 {
+                    c += &self.e[ie][ix][iy] * &v[iy];
+                }
+                e[ie][ix] = c % &m;
+            }
+        }
+        CipherText { e }
+    }
+
     /// generate public key from secret key
-    pub fn new_from_sk(sk: &SecretKey<MOD>) -> PublicKey<MOD> {
+    pub fn new_from_sk(sk: &SecretKey<MOD>) -> Result<PublicKey<MOD>> {
+        let a_invs = sk.inverses()?;
         let enc_fn = |x: Octonion<MOD>, y: Octonion<MOD>| {
             let mut ans = x;
 
             // A_h^-1 ( ... ( A_1^-1 X ) )
-            for a in &sk.a {
-                ans = a.inverse().unwrap() * ans;
+            for a_inv in &a_invs {
+                ans = a_inv.clone() * ans;
             }
 
             ans = y * ans;
@@ -104,33 +307,131 @@ impl<const MOD: &'static str> PublicKey<MOD> {
             return ans;
         };
 
-        let mut e = vec![vec![vec![BigInt::from(0); 8]; 8]; 8];
-        for ix in 0..8 {
-            for iy in 0..8 {
-                let mut x = Octonion::zero();
-                let mut y = Octonion::zero();
-                x[ix] = BigInt::from(1);
-                y[iy] = BigInt::from(1);
-                let result = enc_fn(x, y);
-                for ie in 0..8 {
-                    e[ie][ix][iy] = result[ie].clone();
-                }
+        // the 64 basis pairs are independent, pair k is (x_(k / 8), y_(k % 8))
+        let pair = |k: usize| {
+            let mut x = Octonion::zero();
+            let mut y = Octonion::zero();
+            x[k / 8] = BigInt::from(1);
+            y[k % 8] = BigInt::from(1);
+            enc_fn(x, y)
+        };
+        #[cfg(feature = "parallel")]
+        let results: Vec<Octonion<MOD>> = {
+            use rayon::prelude::*;
+            (0..64usize).into_par_iter().map(pair).collect()
+        };
+        #[cfg(not(feature = "parallel"))]
+        let results: Vec<Octonion<MOD>> = (0..64usize).map(pair).collect();
+
+        let mut e: [[[BigInt; 8]; 8]; 8] = Default::default();
+        for (k, result) in results.into_iter().enumerate() {
+            for ie in 0..8 {
+                e[ie][k / 8][k % 8] = result[ie].clone();
             }
         }
-        return PublicKey { e };
+        Ok(PublicKey { e })
     }
 }
 
+/// tries Schema::try_new gives find_g_h. a try succeeds with probability above 1/16 for
+/// every odd prime, see check_modulus, so all of them fail with probability below 2^-900
+pub const TRY_NEW_MAX_ATTEMPTS: usize = 10_000;
+
 impl<const MOD: &'static str> Schema<MOD> {
     pub fn new() -> Self {
         let (g, h) = Self::find_g_h();
         return Self::new_with_g_h(g, h);
     }
 
+    /// like new, but reject MOD with InvalidModulus unless it is an odd prime.
+    /// check_modulus decides up front whether find_g_h can succeed, and for an odd prime
+    /// the bounded search only gives up with SearchExhausted if the rng is broken
+    pub fn try_new() -> Result<Self> {
+        let m = BigInt::from_str(MOD).map_err(|_| OctonionError::InvalidModulus {
+            modulus: BigInt::from(0),
+            reason: format!("{:?} is not an integer", MOD),
+        })?;
+        check_modulus(&m)?;
+        let solution = Self::find_g_h_bounded(TRY_NEW_MAX_ATTEMPTS)?;
+        Ok(Self::new_with_g_h(solution.g, solution.hs.0))
+    }
+
     pub fn new_with_g_h(g: Octonion<MOD>, h: Octonion<MOD>) -> Self {
         Self { g, h }
     }
 
+    /// like new_with_g_h, but reject g and h that fail verify
+    pub fn try_new_with_g_h(g: Octonion<MOD>, h: Octonion<MOD>) -> Result<Self> {
+        let schema = Self::new_with_g_h(g, h);
+        schema.verify()?;
+        Ok(schema)
+    }
+
+    pub fn g(&self) -> &Octonion<MOD> {
+        &self.g
+    }
+
+    pub fn h(&self) -> &Octonion<MOD> {
+        &self.h
+    }
+
+    /// check the conditions m_to_p depends on.
+    /// Re(p g + u h + v gh + w hg) = p / 2 needs Re(g) = 1/2 and Re(h) = Re(gh) = Re(hg) = 0,
+    /// and N(g) = N(h) = 0 make g^2 = g and h^2 = 0 so products stay in the same form.
+    /// h = 0 is rejected, it leaves the plaintext unmasked
+    pub fn verify(&self) -> Result<()> {
+        let m = BigInt::from_str(MOD).unwrap();
+        let re = |o: &Octonion<MOD>| ((&o.a0 % &m) + &m) % &m;
+        let zero = BigInt::from(0);
+        let half = inverse(BigInt::from(2), m.clone())?;
+
+        for (name, o) in [("g", &self.g), ("h", &self.h)].iter() {
+            for i in 0..8 {
+                if o[i] < zero || o[i] >= m {
+                    return Err(OctonionError::ModulusMismatch {
+                        value: o[i].clone(),
+                        modulus: m,
+                    });
+                }
+            }
+            if o.norm2() != zero {
+                return Err(OctonionError::InvalidSchema(format!(
+                    "N({}) = {}, must be 0",
+                    name,
+                    o.norm2()
+                )));
+            }
+        }
+        if re(&self.g) != half {
+            return Err(OctonionError::InvalidSchema(format!(
+                "Re(g) = {}, must be 1/2 = {}",
+                re(&self.g),
+                half
+            )));
+        }
+        if re(&self.h) != zero {
+            return Err(OctonionError::InvalidSchema(format!(
+                "Re(h) = {}, must be 0",
+                re(&self.h)
+            )));
+        }
+        if self.h.is_zero() {
+            return Err(OctonionError::InvalidSchema("h must not be 0".to_string()));
+        }
+        let gh = self.g.clone() * self.h.clone();
+        let hg = self.h.clone() * self.g.clone();
+        for (name, o) in [("gh", &gh), ("hg", &hg)].iter() {
+            if re(o) != zero {
+                return Err(OctonionError::InvalidSchema(format!(
+                    "Re({}) = {}, must be 0",
+                    name,
+                    re(o)
+                )));
+            }
+        }
+        Ok(())
+    }
+
     pub fn new_plaintext(&self, p: BigInt) -> PlainText<MOD> {
         // TODO: validate Octonion over Fq
         let m = BigInt::from_str(MOD).unwrap();
@@ -139,67 +440,155 @@ impl<const MOD: &'static str> Schema<MOD> {
 
     /// generate secret key and public key
     pub fn gen_sk_pk(&self) -> (SecretKey<MOD>, PublicKey<MOD>) {
+        self.gen_sk_pk_with_rng(&mut rand::thread_rng())
+    }
+
+    /// gen_sk_pk drawing the secret key from rng.
+    /// the public key only depends on the secret key, so a seeded rng gives the same keys
+    /// with or without the parallel feature
+    pub fn gen_sk_pk_with_rng<R: Rng + ?Sized>(
+        &self,
+        rng: &mut R,
+    ) -> (SecretKey<MOD>, PublicKey<MOD>) {
         // TODO: how to determin SecretKey.a.len()
         let h = 56;
         let a = {
             let mut a = Vec::with_capacity(h);
             for _ in 0..h {
-                a.push(gen_rand_octonion_which_has_inv());
+                a.push(gen_rand_octonion_which_has_inv_with_rng(rng));
             }
             a
         };
         let sk = SecretKey { h, a };
-        let pk = PublicKey::new_from_sk(&sk);
+        let pk = PublicKey::new_from_sk(&sk).expect("every A_i has an inverse");
         return (sk, pk);
     }
 
     pub fn encrypt(&self, pt: PlainText<MOD>, pk: &PublicKey<MOD>) -> CipherText<MOD> {
-        let m = BigInt::from_str(MOD).unwrap();
+        self.encrypt_with_rng(pt, pk, &mut rand::thread_rng())
+    }
 
-        let mt = self.p_to_m(pt);
-        let mut e = vec![vec![BigInt::from(0); 8]; 8];
-        for ie in 0..8 {
-            for ix in 0..8 {
-                for iy in 0..8 {
-                    e[ie][ix] += &pk.e[ie][ix][iy] * &mt.value[iy];
-                    e[ie][ix] %= &m;
-                }
-            }
+    /// encrypt drawing u, v, w from rng
+    pub fn encrypt_with_rng<R: Rng + ?Sized>(
+        &self,
+        pt: PlainText<MOD>,
+        pk: &PublicKey<MOD>,
+        rng: &mut R,
+    ) -> CipherText<MOD> {
+        let mt = self.p_to_m_with_rng(pt, rng);
+        pk.apply(&mt.value)
+    }
+
+    /// E(g), the deterministic encryption of 1 with u = v = w = 0.
+    /// compute once per key and pass to CipherText::add_plain
+    pub fn unit(&self, pk: &PublicKey<MOD>) -> CipherText<MOD> {
+        pk.apply(&self.g)
+    }
+
+    pub fn decrypt(&self, ct: CipherText<MOD>, sk: &SecretKey<MOD>) -> Result<PlainText<MOD>> {
+        Ok(self.m_to_p(&self.decrypt_to_m(&ct, sk)?))
+    }
+
+    /// like decrypt, but reject ciphertexts whose mediamtext is not well formed,
+    /// which happens for corrupted or hand built ciphertexts
+    pub fn decrypt_checked(
+        &self,
+        ct: CipherText<MOD>,
+        sk: &SecretKey<MOD>,
+    ) -> Result<PlainText<MOD>> {
+        let mt = self.decrypt_to_m(&ct, sk)?;
+        match mt.decompose(self) {
+            Some((p, _, _)) => Ok(PlainText { value: p }),
+            None => Err(OctonionError::MalformedCiphertext(format!(
+                "mediamtext {} is not in the span of g, h, gh",
+                mt
+            ))),
         }
-        CipherText { e }
     }
 
-    pub fn decrypt(&self, ct: CipherText<MOD>, sk: &SecretKey<MOD>) -> PlainText<MOD> {
-        let m = BigInt::from_str(MOD).unwrap();
+    /// encrypt every plaintext, on all cores with the parallel feature
+    pub fn encrypt_batch(
+        &self,
+        pts: Vec<PlainText<MOD>>,
+        pk: &PublicKey<MOD>,
+    ) -> Vec<CipherText<MOD>> {
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            pts.into_par_iter().map(|pt| self.encrypt(pt, pk)).collect()
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            pts.into_iter().map(|pt| self.encrypt(pt, pk)).collect()
+        }
+    }
+
+    /// decrypt every ciphertext, on all cores with the parallel feature.
+    /// the secret key is prepared once for the whole batch
+    pub fn decrypt_batch(
+        &self,
+        cts: Vec<CipherText<MOD>>,
+        sk: &SecretKey<MOD>,
+    ) -> Result<Vec<PlainText<MOD>>> {
+        let (x, a_invs) = Self::prepare_sk(sk)?;
+        let decrypt = |ct: CipherText<MOD>| self.m_to_p(&Self::decrypt_prepared(&ct, &x, &a_invs));
+        #[cfg(feature = "parallel")]
+        {
+            use rayon::prelude::*;
+            Ok(cts.into_par_iter().map(decrypt).collect())
+        }
+        #[cfg(not(feature = "parallel"))]
+        {
+            Ok(cts.into_iter().map(decrypt).collect())
+        }
+    }
+
+    fn decrypt_to_m(&self, ct: &CipherText<MOD>, sk: &SecretKey<MOD>) -> Result<MediamText<MOD>> {
+        let (x, a_invs) = Self::prepare_sk(sk)?;
+        Ok(Self::decrypt_prepared(ct, &x, &a_invs))
+    }
+
+    /// the parts of decryption that only depend on sk, A_1 ( ... (A_h 1) ) and the A_i^-1
+    fn prepare_sk(sk: &SecretKey<MOD>) -> Result<(Vector<MOD>, Vec<Octonion<MOD>>)> {
+        let a_invs = sk.inverses()?;
 
         // TODO: if rust implements #[feature(fn_traits)], impl Fn for SecretKey
-        let mut mt = Octonion::zero();
         let mut x = Octonion::one();
         // A_1 ( ... (A_h 1) )
         for a in sk.a.iter().rev() {
             x = a.clone() * x;
         }
+        Ok((Vector::from(&x), a_invs))
+    }
 
-        for ie in 0..8 {
-            for ix in 0..8 {
-                mt[ie] += &ct.e[ie][ix] * &x[ix];
-            }
-            mt[ie] %= &m;
-        }
+    fn decrypt_prepared(
+        ct: &CipherText<MOD>,
+        x: &Vector<MOD>,
+        a_invs: &[Octonion<MOD>],
+    ) -> MediamText<MOD> {
+        let mut mt = (&ct.to_matrix() * x).to_octonion().unwrap();
 
         // A_r^-1 ( ... (A_1^-1 pt) )
-        for a in sk.a.iter() {
-            mt = a.inverse().unwrap() * mt;
+        for a_inv in a_invs {
+            mt = a_inv.clone() * mt;
         }
 
-        self.m_to_p(&MediamText { value: mt })
+        MediamText { value: mt }
     }
 
     /// plaintext -> mediamtext
     pub fn p_to_m(&self, p: PlainText<MOD>) -> MediamText<MOD> {
+        self.p_to_m_with_rng(p, &mut rand::thread_rng())
+    }
+
+    /// p_to_m drawing u, v, w from rng
+    pub fn p_to_m_with_rng<R: Rng + ?Sized>(
+        &self,
+        p: PlainText<MOD>,
+        rng: &mut R,
+    ) -> MediamText<MOD> {
         let m = BigInt::from_str(MOD).unwrap();
 
-        let mut rng = rand::thread_rng();
         let u = rng.gen_bigint_range(&BigInt::from(0), &m);
         let v = rng.gen_bigint_range(&BigInt::from(0), &m);
         let w = rng.gen_bigint_range(&BigInt::from(0), &m);
@@ -221,147 +610,280 @@ impl<const MOD: &'static str> Schema<MOD> {
 
     /// find G, H
     pub fn find_g_h() -> (Octonion<MOD>, Octonion<MOD>) {
-        let m = BigInt::from_str(MOD).unwrap();
-        // TODO: if using groebner basis
+        // special case solved by hand, sample_g_h covers the whole solution set
         let mut rng = rand::thread_rng();
-
-        let two = BigInt::from(2);
         loop {
-            let g0: BigInt = BigInt::from(1) * inverse(BigInt::from(2), m.clone());
-            let g1 = rng.gen_bigint_range(&BigInt::from(0), &m);
-            let g2 = rng.gen_bigint_range(&BigInt::from(0), &m);
-            let g3 = rng.gen_bigint_range(&BigInt::from(0), &m);
-            let g4 = rng.gen_bigint_range(&BigInt::from(0), &m);
-            let g5 = rng.gen_bigint_range(&BigInt::from(0), &m);
-
-            let h0: BigInt = BigInt::from(0);
-            let h1 = rng.gen_bigint_range(&BigInt::from(0), &m);
-            let h2 = rng.gen_bigint_range(&BigInt::from(0), &m);
-            let h3 = rng.gen_bigint_range(&BigInt::from(0), &m);
-            let h4 = rng.gen_bigint_range(&BigInt::from(0), &m);
-            let h5 = rng.gen_bigint_range(&BigInt::from(0), &m);
-
-            // find g6, g7, h6, h7
-            let g6 = rng.gen_bigint_range(&BigInt::from(0), &m);
-
-            let mut g7g7 = -(g0.modpow(&two, &m)
-                + g1.modpow(&two, &m)
-                + g2.modpow(&two, &m)
-                + g3.modpow(&two, &m)
-                + g4.modpow(&two, &m)
-                + g5.modpow(&two, &m)
-                + g6.modpow(&two, &m));
-            g7g7 %= &m;
-            if g7g7 < BigInt::default() {
-                g7g7 += &m;
+            if let Ok((g, hs)) = Self::try_find_g_h(&mut rng) {
+                break (g, hs.0);
             }
-            if !is_residue(&g7g7, &m) {
-                // dbg!("omg", g7g7);
-                continue;
+        }
+    }
+
+    /// find_g_h giving up after max_attempts random tries.
+    /// returns both solutions for h and why the other tries were rejected
+    pub fn find_g_h_bounded(max_attempts: usize) -> Result<GHSolution<MOD>> {
+        let mut rng = rand::thread_rng();
+        let mut stats = GHSearchStats::default();
+        while stats.attempts < max_attempts {
+            stats.attempts += 1;
+            match Self::try_find_g_h(&mut rng) {
+                Ok((g, hs)) => return Ok(GHSolution { g, hs, stats }),
+                Err(rejection) => stats.record(rejection),
             }
+        }
+        Err(OctonionError::SearchExhausted {
+            attempts: stats.attempts,
+            reason: stats.to_string(),
+            stats: Some(stats),
+        })
+    }
 
-            // solve
-            // h6h6 + h7h7 = b
-            // g6h6 + g7h7 = d
-
-            let mut b = -(h1.modpow(&two, &m)
-                + h2.modpow(&two, &m)
-                + h3.modpow(&two, &m)
-                + h4.modpow(&two, &m)
-                + h5.modpow(&two, &m));
-            b %= &m;
-            if b < BigInt::default() {
-                b += &m;
+    /// constraints on g and h as polynomials in 16 variables, g_i is x_i and h_i is x_{8 + i}.
+    /// g0 = 1/2, h0 = 0, N(g) = 0, N(h) = 0 and g1 h1 + ... + g7 h7 = 0,
+    /// which make g^2 = g, h^2 = 0 and Re(gh) = 0
+    pub fn g_h_constraints() -> Vec<Polynomial<MOD>> {
+        let m = BigInt::from_str(MOD).unwrap();
+        let x = |i| Polynomial::<MOD>::var(16, i);
+        let half = inverse(BigInt::from(2), m).expect("MOD must be odd");
+
+        let mut norm_g = Polynomial::zero(16);
+        let mut norm_h = Polynomial::zero(16);
+        let mut dot = Polynomial::zero(16);
+        for i in 0..8 {
+            norm_g = &norm_g + &x(i).pow(2);
+            norm_h = &norm_h + &x(8 + i).pow(2);
+            if i > 0 {
+                dot = &dot + &(&x(i) * &x(8 + i));
             }
+        }
+        vec![
+            &x(0) - &Polynomial::constant(16, half),
+            x(8),
+            norm_g,
+            norm_h,
+            dot,
+        ]
+    }
 
-            let c = g6.clone();
-            let c2 = c.modpow(&two, &m);
+    /// every (g, h) with h != 0 satisfying g_h_constraints once the given variables are fixed.
+    /// errors if that leaves the system not zero dimensional
+    pub fn solve_g_h(fixed: &[(usize, BigInt)]) -> Result<Vec<(Octonion<MOD>, Octonion<MOD>)>> {
+        let mut system = Self::g_h_constraints();
+        for (var, value) in fixed {
+            system = system.iter().map(|p| p.substitute(*var, value)).collect();
+        }
+        let vars: Vec<usize> = (0..16)
+            .filter(|i| fixed.iter().all(|(var, _)| var != i))
+            .collect();
+
+        let mut solutions = Vec::new();
+        for solution in polynomial::solve(&system, &vars)? {
+            let mut x = vec![BigInt::from(0); 16];
+            for (var, value) in fixed {
+                x[*var] = value.clone();
+            }
+            for (var, value) in vars.iter().zip(solution) {
+                x[*var] = value;
+            }
+            let g = Vector::new(x[..8].to_vec()).to_octonion().unwrap();
+            let h = Vector::new(x[8..].to_vec()).to_octonion().unwrap();
+            if !h.is_zero() {
+                solutions.push((g, h));
+            }
+        }
+        Ok(solutions)
+    }
 
-            let mut d = -(&g1 * &h1 + &g2 * &h2 + &g3 * &h3 + &g4 * &h4 + &g5 * &h5);
-            d %= &m;
-            if d < BigInt::default() {
-                d += &m;
+    /// sample (g, h) from the whole solution set instead of find_g_h's slice.
+    /// each try solves for g0, h0 and either one g_i and two h_j or two g_i and one h_j,
+    /// chosen at random, with random values for the other coordinates.
+    /// every solution can come out, but not uniformly: a choice of the random coordinates
+    /// with fewer solutions gives each of them more weight
+    pub fn sample_g_h(max_attempts: usize) -> Result<(Octonion<MOD>, Octonion<MOD>)> {
+        let m = BigInt::from_str(MOD).unwrap();
+        let mut rng = rand::thread_rng();
+        for _ in 0..max_attempts {
+            let mut gs: Vec<usize> = (1..8).collect();
+            let mut hs: Vec<usize> = (9..16).collect();
+            gs.shuffle(&mut rng);
+            hs.shuffle(&mut rng);
+            let (n_g, n_h) = if rng.gen() { (1, 2) } else { (2, 1) };
+            let unknowns: Vec<usize> = gs[..n_g].iter().chain(&hs[..n_h]).cloned().collect();
+
+            let fixed: Vec<(usize, BigInt)> = (1..16)
+                .filter(|i| *i != 8 && !unknowns.contains(i))
+                .map(|i| (i, rng.gen_bigint_range(&BigInt::from(0), &m)))
+                .collect();
+            let mut solutions = match Self::solve_g_h(&fixed) {
+                Ok(solutions) => solutions,
+                Err(_) => continue,
+            };
+            if !solutions.is_empty() {
+                let i = rng.gen_range(0, solutions.len());
+                return Ok(solutions.swap_remove(i));
             }
-            let d2 = d.modpow(&two, &m);
+        }
+        Err(OctonionError::SearchExhausted {
+            attempts: max_attempts,
+            reason: "no solution for the random coordinates".to_string(),
+            stats: None,
+        })
+    }
 
-            let e2 = g7g7.clone();
+    /// one random try of find_g_h.
+    /// h6, h7 solve a quadratic, so there are two h for each g
+    fn try_find_g_h<R: Rng + ?Sized>(
+        rng: &mut R,
+    ) -> std::result::Result<(Octonion<MOD>, (Octonion<MOD>, Octonion<MOD>)), GHRejection> {
+        let m = BigInt::from_str(MOD).unwrap();
 
-            let mut pre_h7_key = &d2 * &e2 - (&e2 + &c2) * (&d2 - &b * &c2);
-            pre_h7_key %= &m;
-            if pre_h7_key < BigInt::default() {
-                pre_h7_key += &m;
-            }
-            if !is_residue(&pre_h7_key, &m) {
-                // dbg!("omg", pre_h7_key);
-                continue;
-            }
+        let two = BigInt::from(2);
+        let g0: BigInt = BigInt::from(1) * inverse(BigInt::from(2), m.clone()).unwrap();
+        let g1 = rng.gen_bigint_range(&BigInt::from(0), &m);
+        let g2 = rng.gen_bigint_range(&BigInt::from(0), &m);
+        let g3 = rng.gen_bigint_range(&BigInt::from(0), &m);
+        let g4 = rng.gen_bigint_range(&BigInt::from(0), &m);
+        let g5 = rng.gen_bigint_range(&BigInt::from(0), &m);
+
+        let h0: BigInt = BigInt::from(0);
+        let h1 = rng.gen_bigint_range(&BigInt::from(0), &m);
+        let h2 = rng.gen_bigint_range(&BigInt::from(0), &m);
+        let h3 = rng.gen_bigint_range(&BigInt::from(0), &m);
+        let h4 = rng.gen_bigint_range(&BigInt::from(0), &m);
+        let h5 = rng.gen_bigint_range(&BigInt::from(0), &m);
+
+        // find g6, g7, h6, h7
+        let g6 = rng.gen_bigint_range(&BigInt::from(0), &m);
+        // h6 is solved by dividing by g6, and with g6 = 0 the discriminant below is 0 too,
+        // so reject it before the residue tests count it as something else
+        if g6 == BigInt::from(0) {
+            return Err(GHRejection::G6Zero);
+        }
 
-            let g7: BigInt = sqrt_with_mod(g7g7, m.clone()).unwrap();
-            // dbg!(&g6, &g7);
-            let e = g7.clone();
+        let mut g7g7 = -(g0.modpow(&two, &m)
+            + g1.modpow(&two, &m)
+            + g2.modpow(&two, &m)
+            + g3.modpow(&two, &m)
+            + g4.modpow(&two, &m)
+            + g5.modpow(&two, &m)
+            + g6.modpow(&two, &m));
+        g7g7 %= &m;
+        if g7g7 < BigInt::default() {
+            g7g7 += &m;
+        }
+        if !is_residue(&g7g7, &m) {
+            return Err(GHRejection::G7NotResidue);
+        }
 
-            let h7s = {
-                let e2c2 = (&e2 + &c2) % &m;
-                if e2c2 == BigInt::from(0) {
-                    // e2 + c2 cant be divider
-                    continue;
-                }
+        // solve
+        // h6h6 + h7h7 = b
+        // g6h6 + g7h7 = d
+
+        let mut b = -(h1.modpow(&two, &m)
+            + h2.modpow(&two, &m)
+            + h3.modpow(&two, &m)
+            + h4.modpow(&two, &m)
+            + h5.modpow(&two, &m));
+        b %= &m;
+        if b < BigInt::default() {
+            b += &m;
+        }
 
-                let sqrt = sqrt_with_mod(pre_h7_key, m.clone()).unwrap();
-                let inv_e2c2 = inverse(e2c2, m.clone());
-                let mut h7s = (&d * &e + &sqrt, &d * &e - &sqrt);
-                h7s.0 %= &m;
-                h7s.1 %= &m;
-                if h7s.0 < BigInt::default() {
-                    h7s.0 += &m;
-                }
-                if h7s.1 < BigInt::default() {
-                    h7s.1 += &m;
-                }
-                h7s.0 *= &inv_e2c2;
-                h7s.1 *= &inv_e2c2;
-                h7s.0 %= &m;
-                h7s.1 %= &m;
-                if h7s.0 < BigInt::default() {
-                    h7s.0 += &m;
-                }
-                if h7s.1 < BigInt::default() {
-                    h7s.1 += &m;
-                }
-                h7s
+        let c = g6.clone();
+        let c2 = c.modpow(&two, &m);
+
+        let mut d = -(&g1 * &h1 + &g2 * &h2 + &g3 * &h3 + &g4 * &h4 + &g5 * &h5);
+        d %= &m;
+        if d < BigInt::default() {
+            d += &m;
+        }
+        let d2 = d.modpow(&two, &m);
+
+        let e2 = g7g7.clone();
+
+        let mut pre_h7_key = &d2 * &e2 - (&e2 + &c2) * (&d2 - &b * &c2);
+        pre_h7_key %= &m;
+        if pre_h7_key < BigInt::default() {
+            pre_h7_key += &m;
+        }
+        if !is_residue(&pre_h7_key, &m) {
+            return Err(GHRejection::DiscriminantNotResidue);
+        }
+
+        let g7: BigInt = match sqrt_with_mod(g7g7, m.clone()) {
+            Ok(g7) => g7,
+            Err(_) => return Err(GHRejection::G7NotResidue),
+        };
+        // dbg!(&g6, &g7);
+        let e = g7.clone();
+
+        let h7s = {
+            let e2c2 = (&e2 + &c2) % &m;
+            if e2c2 == BigInt::from(0) {
+                // e2 + c2 cant be divider
+                return Err(GHRejection::DegenerateDenominator);
+            }
+
+            let sqrt = match sqrt_with_mod(pre_h7_key, m.clone()) {
+                Ok(sqrt) => sqrt,
+                Err(_) => return Err(GHRejection::DiscriminantNotResidue),
+            };
+            let inv_e2c2 = match inverse(e2c2, m.clone()) {
+                Ok(inv) => inv,
+                Err(_) => return Err(GHRejection::DegenerateDenominator),
             };
+            let mut h7s = (&d * &e + &sqrt, &d * &e - &sqrt);
+            h7s.0 %= &m;
+            h7s.1 %= &m;
+            if h7s.0 < BigInt::default() {
+                h7s.0 += &m;
+            }
+            if h7s.1 < BigInt::default() {
+                h7s.1 += &m;
+            }
+            h7s.0 *= &inv_e2c2;
+            h7s.1 *= &inv_e2c2;
+            h7s.0 %= &m;
+            h7s.1 %= &m;
+            if h7s.0 < BigInt::default() {
+                h7s.0 += &m;
+            }
+            if h7s.1 < BigInt::default() {
+                h7s.1 += &m;
+            }
+            h7s
+        };
 
-            let h6s = {
-                let inv_c = inverse(c.clone(), m.clone());
-                let mut h6s = ((&d - &h7s.0 * &e) * &inv_c, (&d - &h7s.1 * &e) * &inv_c);
-                h6s.0 %= &m;
-                h6s.1 %= &m;
-                if h6s.0 < BigInt::default() {
-                    h6s.0 += &m;
-                }
-                if h6s.1 < BigInt::default() {
-                    h6s.1 += &m;
-                }
-                h6s
+        let h6s = {
+            let inv_c = match inverse(c.clone(), m.clone()) {
+                Ok(inv) => inv,
+                // g6 = 0
+                Err(_) => return Err(GHRejection::G6Zero),
             };
+            let mut h6s = ((&d - &h7s.0 * &e) * &inv_c, (&d - &h7s.1 * &e) * &inv_c);
+            h6s.0 %= &m;
+            h6s.1 %= &m;
+            if h6s.0 < BigInt::default() {
+                h6s.0 += &m;
+            }
+            if h6s.1 < BigInt::default() {
+                h6s.1 += &m;
+            }
+            h6s
+        };
 
-            // dbg!(&h6s, &h7s);
-            let g: Octonion<MOD> = Octonion::new_with_bigint(g0, g1, g2, g3, g4, g5, g6, g7);
-            let hs0: Octonion<MOD> = Octonion::new_with_bigint(
-                h0.clone(),
-                h1.clone(),
-                h2.clone(),
-                h3.clone(),
-                h4.clone(),
-                h5.clone(),
-                h6s.0,
-                h7s.0,
-            );
-            // TODO: can i use something? zkp...
-            let _: Octonion<MOD> = Octonion::new_with_bigint(h0, h1, h2, h3, h4, h5, h6s.1, h7s.1);
-
-            // break (g, hs1);
-            break (g, hs0);
-        }
+        let g: Octonion<MOD> = Octonion::new_with_bigint(g0, g1, g2, g3, g4, g5, g6, g7);
+        let hs0: Octonion<MOD> = Octonion::new_with_bigint(
+            h0.clone(),
+            h1.clone(),
+            h2.clone(),
+            h3.clone(),
+            h4.clone(),
+            h5.clone(),
+            h6s.0,
+            h7s.0,
+        );
+        let hs1: Octonion<MOD> = Octonion::new_with_bigint(h0, h1, h2, h3, h4, h5, h6s.1, h7s.1);
+        Ok((g, (hs0, hs1)))
     }
 }
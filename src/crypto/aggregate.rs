@@ -0,0 +1,139 @@
+//! streaming aggregation of encrypted readings.
+//!
+//! the aggregator keeps three running ciphertexts, count, sum and sum of squares, so memory
+//! does not grow with the number of inputs. readings are signed integers, see encoding::signed.
+//! the sum of squares has to stay below p, n max|x|^2 < p, or it wraps around.
+use num_bigint::BigInt;
+use num_traits::ToPrimitive;
+
+use crate::crypto::cipher_text::CipherText;
+use crate::crypto::{PublicKey, Schema, SecretKey};
+use crate::encoding::signed;
+use crate::error::Result;
+
+/// running count, sum and sum of squares of ciphertexts
+#[derive(Debug, Clone)]
+pub struct Aggregator<const MOD: &'static str> {
+    /// Schema::unit, added once per reading to the count
+    unit: CipherText<MOD>,
+    count: CipherText<MOD>,
+    sum: CipherText<MOD>,
+    sum_of_squares: CipherText<MOD>,
+    /// readings pushed so far, known to the aggregator anyway
+    len: u64,
+}
+
+/// decrypted aggregates, mean and variance are finished in the clear
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Statistics {
+    pub count: BigInt,
+    pub sum: BigInt,
+    pub sum_of_squares: BigInt,
+}
+
+impl<const MOD: &'static str> Aggregator<MOD> {
+    pub fn new(schema: &Schema<MOD>, pk: &PublicKey<MOD>) -> Self {
+        Self::with_unit(schema.unit(pk))
+    }
+
+    /// unit is Schema::unit
+    pub fn with_unit(unit: CipherText<MOD>) -> Self {
+        Self {
+            unit,
+            count: CipherText::zero(),
+            sum: CipherText::zero(),
+            sum_of_squares: CipherText::zero(),
+            len: 0,
+        }
+    }
+
+    /// add one reading, one ciphertext multiplication
+    pub fn push(&mut self, ct: &CipherText<MOD>) {
+        self.count = &self.count + &self.unit;
+        self.sum = &self.sum + ct;
+        self.sum_of_squares = &self.sum_of_squares + &(ct * ct);
+        self.len += 1;
+    }
+
+    /// fold in an aggregator over other readings, e.g. from another worker
+    pub fn merge(&mut self, other: &Self) {
+        self.count = &self.count + &other.count;
+        self.sum = &self.sum + &other.sum;
+        self.sum_of_squares = &self.sum_of_squares + &other.sum_of_squares;
+        self.len += other.len;
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// ciphertext of the number of readings
+    pub fn count(&self) -> &CipherText<MOD> {
+        &self.count
+    }
+
+    /// ciphertext of the sum of the readings
+    pub fn sum(&self) -> &CipherText<MOD> {
+        &self.sum
+    }
+
+    /// ciphertext of the sum of the squared readings
+    pub fn sum_of_squares(&self) -> &CipherText<MOD> {
+        &self.sum_of_squares
+    }
+
+    /// decrypt the aggregates, done by the key holder
+    pub fn decrypt(&self, schema: &Schema<MOD>, sk: &SecretKey<MOD>) -> Result<Statistics> {
+        Ok(Statistics {
+            count: schema.decrypt(self.count.clone(), sk)?.value,
+            sum: signed::decode(&schema.decrypt(self.sum.clone(), sk)?),
+            sum_of_squares: schema.decrypt(self.sum_of_squares.clone(), sk)?.value,
+        })
+    }
+}
+
+impl<const MOD: &'static str> Extend<CipherText<MOD>> for Aggregator<MOD> {
+    fn extend<I: IntoIterator<Item = CipherText<MOD>>>(&mut self, iter: I) {
+        for ct in iter {
+            self.push(&ct);
+        }
+    }
+}
+
+impl Statistics {
+    /// sum / count, None without readings
+    pub fn mean(&self) -> Option<f64> {
+        if self.count == BigInt::from(0) {
+            return None;
+        }
+        Some(self.sum.to_f64()? / self.count.to_f64()?)
+    }
+
+    /// population variance (n sum x^2 - (sum x)^2) / n^2, None without readings
+    pub fn variance(&self) -> Option<f64> {
+        if self.count == BigInt::from(0) {
+            return None;
+        }
+        // exact numerator, the difference of two large floats would cancel
+        let numerator = &self.count * &self.sum_of_squares - &self.sum * &self.sum;
+        Some(numerator.to_f64()? / (&self.count * &self.count).to_f64()?)
+    }
+
+    /// unbiased variance (n sum x^2 - (sum x)^2) / (n (n - 1)), None for fewer than 2 readings
+    pub fn sample_variance(&self) -> Option<f64> {
+        if self.count < BigInt::from(2) {
+            return None;
+        }
+        let numerator = &self.count * &self.sum_of_squares - &self.sum * &self.sum;
+        let denominator: BigInt = &self.count * (&self.count - 1);
+        Some(numerator.to_f64()? / denominator.to_f64()?)
+    }
+
+    pub fn std_dev(&self) -> Option<f64> {
+        self.variance().map(f64::sqrt)
+    }
+}
@@ -0,0 +1,202 @@
+//! boolean gates on ciphertexts of bits.
+//!
+//! a bit is a ciphertext of 0 or 1, the gates are polynomials that agree with the boolean
+//! functions on {0, 1}. the result of a gate on anything else is meaningless.
+//! multi-bit numbers are slices of bits, least significant bit first.
+use num_bigint::BigInt;
+
+use crate::crypto::cipher_text::CipherText;
+use crate::error::{OctonionError, Result};
+
+/// gates need the ciphertext of 1 for NOT and constants
+#[derive(Debug, Clone)]
+pub struct BooleanGates<const MOD: &'static str> {
+    /// Schema::unit
+    unit: CipherText<MOD>,
+}
+
+impl<const MOD: &'static str> BooleanGates<MOD> {
+    /// unit is Schema::unit
+    pub fn new(unit: CipherText<MOD>) -> Self {
+        Self { unit }
+    }
+
+    /// ciphertext of 1
+    pub fn one(&self) -> CipherText<MOD> {
+        self.unit.clone()
+    }
+
+    /// ciphertext of 0
+    pub fn zero(&self) -> CipherText<MOD> {
+        CipherText::zero()
+    }
+
+    /// ciphertext of b
+    pub fn constant(&self, b: bool) -> CipherText<MOD> {
+        if b {
+            self.one()
+        } else {
+            self.zero()
+        }
+    }
+
+    /// 1 - a
+    pub fn not(&self, a: &CipherText<MOD>) -> CipherText<MOD> {
+        &self.unit - a
+    }
+
+    /// a b
+    pub fn and(&self, a: &CipherText<MOD>, b: &CipherText<MOD>) -> CipherText<MOD> {
+        a * b
+    }
+
+    /// a + b - a b
+    pub fn or(&self, a: &CipherText<MOD>, b: &CipherText<MOD>) -> CipherText<MOD> {
+        &(a + b) - &(a * b)
+    }
+
+    /// a + b - 2 a b
+    pub fn xor(&self, a: &CipherText<MOD>, b: &CipherText<MOD>) -> CipherText<MOD> {
+        &(a + b) - &(a * b).mul_scalar(&BigInt::from(2))
+    }
+
+    /// 1 - a b
+    pub fn nand(&self, a: &CipherText<MOD>, b: &CipherText<MOD>) -> CipherText<MOD> {
+        self.not(&self.and(a, b))
+    }
+
+    /// 1 - (a xor b)
+    pub fn xnor(&self, a: &CipherText<MOD>, b: &CipherText<MOD>) -> CipherText<MOD> {
+        self.not(&self.xor(a, b))
+    }
+
+    /// a if sel else b, b + sel (a - b)
+    pub fn mux(
+        &self,
+        sel: &CipherText<MOD>,
+        a: &CipherText<MOD>,
+        b: &CipherText<MOD>,
+    ) -> CipherText<MOD> {
+        b + &(sel * &(a - b))
+    }
+
+    /// (sum, carry) of a + b + c
+    pub fn full_adder(
+        &self,
+        a: &CipherText<MOD>,
+        b: &CipherText<MOD>,
+        c: &CipherText<MOD>,
+    ) -> (CipherText<MOD>, CipherText<MOD>) {
+        let ab = self.xor(a, b);
+        let sum = self.xor(&ab, c);
+        // majority, the two products can't both be 1
+        let carry = &self.and(a, b) + &self.and(&ab, c);
+        (sum, carry)
+    }
+
+    /// ripple carry a + b, n + 1 bits for n bit inputs
+    pub fn add(
+        &self,
+        a: &[CipherText<MOD>],
+        b: &[CipherText<MOD>],
+    ) -> Result<Vec<CipherText<MOD>>> {
+        check_len(a, b)?;
+        let mut carry = self.zero();
+        let mut ans = Vec::with_capacity(a.len() + 1);
+        for (x, y) in a.iter().zip(b.iter()) {
+            let (sum, c) = self.full_adder(x, y, &carry);
+            ans.push(sum);
+            carry = c;
+        }
+        ans.push(carry);
+        Ok(ans)
+    }
+
+    /// a - b mod 2^n, by a + not(b) + 1
+    pub fn sub(
+        &self,
+        a: &[CipherText<MOD>],
+        b: &[CipherText<MOD>],
+    ) -> Result<Vec<CipherText<MOD>>> {
+        check_len(a, b)?;
+        let mut carry = self.one();
+        let mut ans = Vec::with_capacity(a.len());
+        for (x, y) in a.iter().zip(b.iter()) {
+            let (sum, c) = self.full_adder(x, &self.not(y), &carry);
+            ans.push(sum);
+            carry = c;
+        }
+        Ok(ans)
+    }
+
+    /// a == b
+    pub fn eq(&self, a: &[CipherText<MOD>], b: &[CipherText<MOD>]) -> Result<CipherText<MOD>> {
+        check_len(a, b)?;
+        let mut ans = self.one();
+        for (x, y) in a.iter().zip(b.iter()) {
+            ans = self.and(&ans, &self.xnor(x, y));
+        }
+        Ok(ans)
+    }
+
+    /// a < b as unsigned numbers
+    pub fn lt(&self, a: &[CipherText<MOD>], b: &[CipherText<MOD>]) -> Result<CipherText<MOD>> {
+        check_len(a, b)?;
+        // from the least significant bit up, a differing bit overrides the lower ones
+        let mut ans = self.zero();
+        for (x, y) in a.iter().zip(b.iter()) {
+            ans = self.mux(&self.xor(x, y), y, &ans);
+        }
+        Ok(ans)
+    }
+
+    /// a <= b as unsigned numbers
+    pub fn le(&self, a: &[CipherText<MOD>], b: &[CipherText<MOD>]) -> Result<CipherText<MOD>> {
+        Ok(self.not(&self.lt(b, a)?))
+    }
+
+    /// a if sel else b, bitwise
+    pub fn mux_bits(
+        &self,
+        sel: &CipherText<MOD>,
+        a: &[CipherText<MOD>],
+        b: &[CipherText<MOD>],
+    ) -> Result<Vec<CipherText<MOD>>> {
+        check_len(a, b)?;
+        Ok(a.iter()
+            .zip(b.iter())
+            .map(|(x, y)| self.mux(sel, x, y))
+            .collect())
+    }
+
+    /// min(a, b) as unsigned numbers
+    pub fn min(
+        &self,
+        a: &[CipherText<MOD>],
+        b: &[CipherText<MOD>],
+    ) -> Result<Vec<CipherText<MOD>>> {
+        let lt = self.lt(a, b)?;
+        self.mux_bits(&lt, a, b)
+    }
+
+    /// max(a, b) as unsigned numbers
+    pub fn max(
+        &self,
+        a: &[CipherText<MOD>],
+        b: &[CipherText<MOD>],
+    ) -> Result<Vec<CipherText<MOD>>> {
+        let lt = self.lt(a, b)?;
+        self.mux_bits(&lt, b, a)
+    }
+}
+
+fn check_len<const MOD: &'static str>(a: &[CipherText<MOD>], b: &[CipherText<MOD>]) -> Result<()> {
+    if a.len() != b.len() {
+        return Err(OctonionError::DimensionMismatch(format!(
+            "{} bits and {} bits",
+            a.len(),
+            b.len()
+        )));
+    }
+    Ok(())
+}
@@ -1,14 +1,173 @@
-use std::{fmt::Display, fmt::Formatter, ops::Add, ops::Mul, str::FromStr};
+use std::{
+    fmt::Display, fmt::Formatter, iter::Sum, ops::Add, ops::Index, ops::Mul, ops::Neg, ops::Sub,
+    str::FromStr,
+};
 
 use num_bigint::BigInt;
+use rand::Rng;
 
-use crate::types::Octonion;
+use crate::crypto::vector::tree_sum;
+use crate::crypto::{PlainText, PublicKey, Schema};
+use crate::error::{OctonionError, Result};
+use crate::linalg::Matrix;
 
 #[derive(Debug, Clone)]
 pub struct CipherText<const MOD: &'static str> {
     /// coefficients f: O -> O
     /// e[i][x] ((e00, e01, ..., e07), ..., (e70, e71, ..., e77))
-    pub e: Vec<Vec<BigInt>>,
+    pub(crate) e: [[BigInt; 8]; 8],
+}
+
+impl<const MOD: &'static str> CipherText<MOD> {
+    /// build a ciphertext from raw coefficients.
+    /// e must be 8 x 8 and every coefficient must be in [0, MOD).
+    pub fn new(e: Vec<Vec<BigInt>>) -> Result<Self> {
+        let m = BigInt::from_str(MOD).unwrap();
+        if e.len() != 8 {
+            return Err(OctonionError::MalformedCiphertext(format!(
+                "must have 8 rows, got {}",
+                e.len()
+            )));
+        }
+
+        let mut coefficients: [[BigInt; 8]; 8] = Default::default();
+        for (ie, row) in e.into_iter().enumerate() {
+            if row.len() != 8 {
+                return Err(OctonionError::MalformedCiphertext(format!(
+                    "row {} must have 8 columns, got {}",
+                    ie,
+                    row.len()
+                )));
+            }
+            for (ix, c) in row.into_iter().enumerate() {
+                if c < BigInt::from(0) || c >= m {
+                    return Err(OctonionError::ModulusMismatch {
+                        value: c,
+                        modulus: m,
+                    });
+                }
+                coefficients[ie][ix] = c;
+            }
+        }
+        Ok(CipherText { e: coefficients })
+    }
+
+    /// e[ie][ix]
+    pub fn coefficients(&self) -> &[[BigInt; 8]; 8] {
+        &self.e
+    }
+
+    /// the map O -> O as an 8 x 8 matrix
+    pub fn to_matrix(&self) -> Matrix<MOD> {
+        let data = self.e.iter().flat_map(|row| row.iter().cloned()).collect();
+        Matrix::new(8, 8, data).unwrap()
+    }
+
+    /// build a ciphertext from an 8 x 8 matrix
+    pub fn from_matrix(a: &Matrix<MOD>) -> Result<Self> {
+        if a.rows() != 8 || a.cols() != 8 {
+            return Err(OctonionError::MalformedCiphertext(format!(
+                "must be 8 x 8, got {} x {}",
+                a.rows(),
+                a.cols()
+            )));
+        }
+        let mut e: [[BigInt; 8]; 8] = Default::default();
+        for ie in 0..8 {
+            for ix in 0..8 {
+                e[ie][ix] = a[(ie, ix)].clone();
+            }
+        }
+        Ok(CipherText { e })
+    }
+
+    /// decrypts to k * p, no public key needed
+    pub fn mul_scalar(&self, k: &BigInt) -> Self {
+        CipherText::from_matrix(&self.to_matrix().scale(k)).unwrap()
+    }
+
+    /// decrypts to pt * p, same as mul_scalar
+    pub fn mul_plain(&self, pt: &PlainText<MOD>) -> Self {
+        self.mul_scalar(&pt.value)
+    }
+
+    /// decrypts to p + pt.
+    /// unit is Schema::unit, the constant is added as pt * E(g) so the mediamtext stays
+    /// in the span of g, h, gh, hg and later products still decrypt.
+    /// adding pt / 2 * I would decrypt right once but break products.
+    pub fn add_plain(&self, pt: &PlainText<MOD>, unit: &CipherText<MOD>) -> Self {
+        self + &unit.mul_scalar(&pt.value)
+    }
+
+    /// same plaintext, fresh looking coefficients.
+    /// adds an encryption of 0 with new u, v, w, so results of the same computation
+    /// can't be linked by their coefficients any more
+    pub fn rerandomize<R: Rng + ?Sized>(
+        &self,
+        pk: &PublicKey<MOD>,
+        schema: &Schema<MOD>,
+        rng: &mut R,
+    ) -> Self {
+        self + &schema.encrypt_with_rng(
+            PlainText {
+                value: BigInt::from(0),
+            },
+            pk,
+            rng,
+        )
+    }
+
+    /// ciphertext of 0, the zero matrix
+    pub fn zero() -> Self {
+        CipherText {
+            e: Default::default(),
+        }
+    }
+
+    /// decrypts to p^e, by squaring.
+    /// unit is Schema::unit, returned for e = 0 since the identity matrix decrypts to 2
+    pub fn pow(&self, e: u64, unit: &CipherText<MOD>) -> Self {
+        if e == 0 {
+            return unit.clone();
+        }
+        let mut base = self.to_matrix();
+        let mut ans: Option<Matrix<MOD>> = None;
+        let mut e = e;
+        while e > 0 {
+            if e % 2 == 1 {
+                ans = Some(match ans {
+                    Some(ans) => &ans * &base,
+                    None => base.clone(),
+                });
+            }
+            e /= 2;
+            if e > 0 {
+                base = &base * &base;
+            }
+        }
+        CipherText::from_matrix(&ans.unwrap()).unwrap()
+    }
+
+    /// decrypts to coeffs[0] + coeffs[1] p + ... + coeffs[n] p^n, by horner's rule.
+    /// unit is Schema::unit, needed for the constant term
+    pub fn eval_poly(&self, coeffs: &[BigInt], unit: &CipherText<MOD>) -> Self {
+        let (last, rest) = match coeffs.split_last() {
+            Some(split) => split,
+            None => return CipherText::zero(),
+        };
+        let mut acc = unit.mul_scalar(last);
+        for c in rest.iter().rev() {
+            acc = (&acc * self).add_plain(&PlainText { value: c.clone() }, unit);
+        }
+        acc
+    }
+}
+
+impl<const MOD: &'static str> Index<(usize, usize)> for CipherText<MOD> {
+    type Output = BigInt;
+    fn index(&self, (ie, ix): (usize, usize)) -> &BigInt {
+        &self.e[ie][ix]
+    }
 }
 
 impl<const MOD: &'static str> Display for CipherText<MOD> {
@@ -28,29 +187,7 @@ impl<const MOD: &'static str> Display for CipherText<MOD> {
 impl<'a, const MOD: &'static str> Add<&CipherText<MOD>> for &'a CipherText<MOD> {
     type Output = CipherText<MOD>;
     fn add(self, rhs: &CipherText<MOD>) -> CipherText<MOD> {
-        let m = BigInt::from_str(MOD).unwrap();
-        let enc_fn = |x: Octonion<MOD>| -> Octonion<MOD> {
-            let mut ans = Octonion::zero();
-            for ie in 0..8 {
-                for ix in 0..8 {
-                    ans[ie] += &self.e[ie][ix] * &x[ix];
-                    ans[ie] += &rhs.e[ie][ix] * &x[ix];
-                    ans[ie] %= &m;
-                }
-            }
-            return ans;
-        };
-
-        let mut e = vec![vec![BigInt::from(0); 8]; 8];
-        for ix in 0..8 {
-            let mut x = Octonion::zero();
-            x[ix] = BigInt::from(1);
-            let ans = enc_fn(x);
-            for ie in 0..8 {
-                e[ie][ix] = ans[ie].clone();
-            }
-        }
-        return CipherText { e };
+        CipherText::from_matrix(&(&self.to_matrix() + &rhs.to_matrix())).unwrap()
     }
 }
 
@@ -58,35 +195,47 @@ impl<'a, const MOD: &'static str> Add<&CipherText<MOD>> for &'a CipherText<MOD>
 impl<'a, const MOD: &'static str> Mul<&CipherText<MOD>> for &'a CipherText<MOD> {
     type Output = CipherText<MOD>;
     fn mul(self, rhs: &CipherText<MOD>) -> CipherText<MOD> {
-        let m = BigInt::from_str(MOD).unwrap();
-        let enc_fn = |x: Octonion<MOD>| -> Octonion<MOD> {
-            let mut ans_rhs: Octonion<MOD> = Octonion::zero();
-            for ie in 0..8 {
-                for ix in 0..8 {
-                    ans_rhs[ie] += &rhs.e[ie][ix] * &x[ix];
-                    ans_rhs[ie] %= &m;
-                }
-            }
+        CipherText::from_matrix(&(&self.to_matrix() * &rhs.to_matrix())).unwrap()
+    }
+}
 
-            let mut ans_lhs = Octonion::zero();
-            for ie in 0..8 {
-                for ix in 0..8 {
-                    ans_lhs[ie] += &self.e[ie][ix] * &ans_rhs[ix];
-                    ans_lhs[ie] %= &m;
-                }
-            }
-            return ans_lhs;
-        };
+impl<const MOD: &'static str> Neg for &CipherText<MOD> {
+    type Output = CipherText<MOD>;
+    fn neg(self) -> CipherText<MOD> {
+        CipherText::from_matrix(&(-&self.to_matrix())).unwrap()
+    }
+}
 
-        let mut e = vec![vec![BigInt::from(0); 8]; 8];
-        for ix in 0..8 {
-            let mut x = Octonion::zero();
-            x[ix] = BigInt::from(1);
-            let ans = enc_fn(x);
-            for ie in 0..8 {
-                e[ie][ix] = ans[ie].clone();
-            }
-        }
-        return CipherText { e };
+impl<const MOD: &'static str> Neg for CipherText<MOD> {
+    type Output = CipherText<MOD>;
+    fn neg(self) -> CipherText<MOD> {
+        -&self
+    }
+}
+
+impl<const MOD: &'static str> Sub<&CipherText<MOD>> for &CipherText<MOD> {
+    type Output = CipherText<MOD>;
+    fn sub(self, rhs: &CipherText<MOD>) -> CipherText<MOD> {
+        CipherText::from_matrix(&(&self.to_matrix() - &rhs.to_matrix())).unwrap()
+    }
+}
+
+impl<const MOD: &'static str> Sub for CipherText<MOD> {
+    type Output = CipherText<MOD>;
+    fn sub(self, rhs: CipherText<MOD>) -> CipherText<MOD> {
+        &self - &rhs
+    }
+}
+
+/// tree_sum, in parallel with the parallel feature
+impl<const MOD: &'static str> Sum for CipherText<MOD> {
+    fn sum<I: Iterator<Item = CipherText<MOD>>>(iter: I) -> CipherText<MOD> {
+        tree_sum(iter.collect())
+    }
+}
+
+impl<'a, const MOD: &'static str> Sum<&'a CipherText<MOD>> for CipherText<MOD> {
+    fn sum<I: Iterator<Item = &'a CipherText<MOD>>>(iter: I) -> CipherText<MOD> {
+        tree_sum(iter.cloned().collect())
     }
 }
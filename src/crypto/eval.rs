@@ -0,0 +1,111 @@
+//! evaluation of multivariate polynomials on ciphertexts.
+use std::collections::HashMap;
+
+use num_bigint::BigInt;
+
+use crate::crypto::cipher_text::CipherText;
+use crate::crypto::PlainText;
+use crate::error::{OctonionError, Result};
+use crate::polynomial::{Monomial, Polynomial};
+
+/// powers x_i^e of the inputs, memoized so monomials share them
+#[derive(Debug, Clone)]
+pub struct PowerCache<'a, const MOD: &'static str> {
+    inputs: &'a [CipherText<MOD>],
+    /// Schema::unit, x_i^0
+    unit: &'a CipherText<MOD>,
+    powers: Vec<HashMap<u64, CipherText<MOD>>>,
+    multiplications: usize,
+}
+
+impl<'a, const MOD: &'static str> PowerCache<'a, MOD> {
+    /// unit is Schema::unit
+    pub fn new(inputs: &'a [CipherText<MOD>], unit: &'a CipherText<MOD>) -> Self {
+        Self {
+            inputs,
+            unit,
+            powers: vec![HashMap::new(); inputs.len()],
+            multiplications: 0,
+        }
+    }
+
+    /// x_i^e, from x_i^(e / 2) or x_i^(e - 1). x_i^0 is the unit
+    pub fn pow(&mut self, i: usize, e: u64) -> CipherText<MOD> {
+        if e == 0 {
+            return self.unit.clone();
+        }
+        if e == 1 {
+            return self.inputs[i].clone();
+        }
+        if let Some(ct) = self.powers[i].get(&e) {
+            return ct.clone();
+        }
+        let ct = if e.is_multiple_of(2) {
+            let half = self.pow(i, e / 2);
+            &half * &half
+        } else {
+            &self.pow(i, e - 1) * &self.inputs[i]
+        };
+        self.multiplications += 1;
+        self.powers[i].insert(e, ct.clone());
+        ct
+    }
+
+    /// ciphertext multiplications done so far
+    pub fn multiplications(&self) -> usize {
+        self.multiplications
+    }
+}
+
+/// decrypts to the sum of c x^mono over monomials, x being the plaintexts of inputs.
+/// unit is Schema::unit, needed for a constant term
+pub fn eval_monomials<const MOD: &'static str>(
+    inputs: &[CipherText<MOD>],
+    monomials: &[(Monomial, BigInt)],
+    unit: &CipherText<MOD>,
+) -> Result<CipherText<MOD>> {
+    let mut cache = PowerCache::new(inputs, unit);
+    let mut acc = CipherText::zero();
+    for (mono, c) in monomials {
+        if mono.len() != inputs.len() {
+            return Err(OctonionError::DimensionMismatch(format!(
+                "monomial of {} variables for {} inputs",
+                mono.len(),
+                inputs.len()
+            )));
+        }
+        let mut term: Option<CipherText<MOD>> = None;
+        for (i, e) in mono.iter().enumerate().filter(|(_, e)| **e > 0) {
+            let power = cache.pow(i, *e as u64);
+            term = Some(match term {
+                Some(term) => &term * &power,
+                None => power,
+            });
+        }
+        acc = match term {
+            Some(term) => &acc + &term.mul_scalar(c),
+            None => acc.add_plain(&PlainText { value: c.clone() }, unit),
+        };
+    }
+    Ok(acc)
+}
+
+/// eval_monomials with the terms of poly, which must have one variable per input
+pub fn eval_polynomial<const MOD: &'static str>(
+    poly: &Polynomial<MOD>,
+    inputs: &[CipherText<MOD>],
+    unit: &CipherText<MOD>,
+) -> Result<CipherText<MOD>> {
+    if poly.nvars() != inputs.len() {
+        return Err(OctonionError::DimensionMismatch(format!(
+            "polynomial in {} variables for {} inputs",
+            poly.nvars(),
+            inputs.len()
+        )));
+    }
+    let monomials: Vec<_> = poly
+        .terms()
+        .map(|(mono, c)| (mono.clone(), c.clone()))
+        .collect();
+    eval_monomials(inputs, &monomials, unit)
+}
@@ -30,7 +30,7 @@ fn test_encrypt_decrypt(pt: PlainText<M>) -> bool {
     let schema = Schema::new();
     let (sk, pk) = schema.gen_sk_pk();
     let ct = schema.encrypt(pt.clone(), &pk);
-    let pt_hat = schema.decrypt(ct, &sk);
+    let pt_hat = schema.decrypt(ct, &sk).unwrap();
     if pt.value != pt_hat.value {
         println!("sk: {}", sk);
         println!("pk: {}", pk);
@@ -51,7 +51,7 @@ fn test_encrypt_decrypt_add(lhs_pt: PlainText<M>, rhs_pt: PlainText<M>) -> bool
     let lhs_ct = schema.encrypt(lhs_pt.clone(), &pk);
     let rhs_ct = schema.encrypt(rhs_pt.clone(), &pk);
     let add_ct = &lhs_ct + &rhs_ct;
-    let add_pt_hat = schema.decrypt(add_ct, &sk);
+    let add_pt_hat = schema.decrypt(add_ct, &sk).unwrap();
     let ans = (&lhs_pt.value + &rhs_pt.value) % &m;
 
     if ans != add_pt_hat.value {
@@ -76,10 +76,16 @@ fn test_encrypt_decrypt_mul(lhs_pt: PlainText<M>, rhs_pt: PlainText<M>) -> bool
     let (sk, pk) = schema.gen_sk_pk();
     let lhs_ct = schema.encrypt(lhs_pt.clone(), &pk);
     let rhs_ct = schema.encrypt(rhs_pt.clone(), &pk);
-    assert_eq!(schema.decrypt(lhs_ct.clone(), &sk).value, lhs_pt.value);
-    assert_eq!(schema.decrypt(rhs_ct.clone(), &sk).value, rhs_pt.value);
+    assert_eq!(
+        schema.decrypt(lhs_ct.clone(), &sk).unwrap().value,
+        lhs_pt.value
+    );
+    assert_eq!(
+        schema.decrypt(rhs_ct.clone(), &sk).unwrap().value,
+        rhs_pt.value
+    );
     let mul_ct = &lhs_ct * &rhs_ct;
-    let mul_pt_hat = schema.decrypt(mul_ct, &sk);
+    let mul_pt_hat = schema.decrypt(mul_ct, &sk).unwrap();
     let ans = (&lhs_pt.value * &rhs_pt.value) % &m;
 
     if ans != mul_pt_hat.value {
@@ -95,3 +101,852 @@ fn test_encrypt_decrypt_mul(lhs_pt: PlainText<M>, rhs_pt: PlainText<M>) -> bool
     }
     ans == mul_pt_hat.value
 }
+
+#[quickcheck]
+fn test_ciphertext_new(pt: PlainText<M>) -> bool {
+    let m = BigInt::from_str(M).unwrap();
+
+    let schema = Schema::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let ct = schema.encrypt(pt.clone(), &pk);
+    let raw: Vec<Vec<BigInt>> = ct.coefficients().iter().map(|row| row.to_vec()).collect();
+
+    let mut too_short = raw.clone();
+    too_short[3].pop();
+    let mut out_of_range = raw.clone();
+    out_of_range[0][0] = m.clone();
+    let mut negative = raw.clone();
+    negative[7][7] = BigInt::from(-1);
+    if CipherText::<M>::new(too_short).is_ok()
+        || CipherText::<M>::new(out_of_range).is_ok()
+        || CipherText::<M>::new(negative).is_ok()
+        || CipherText::<M>::new(raw[..7].to_vec()).is_ok()
+    {
+        return false;
+    }
+
+    let rebuilt = CipherText::<M>::new(raw).unwrap();
+    schema.decrypt(rebuilt, &sk).unwrap().value == pt.value
+}
+
+#[test]
+fn test_public_key_new() {
+    let m = BigInt::from_str(M).unwrap();
+
+    let schema = Schema::<M>::new();
+    let (_, pk) = schema.gen_sk_pk();
+    let raw: Vec<Vec<Vec<BigInt>>> = pk
+        .coefficients()
+        .iter()
+        .map(|plane| plane.iter().map(|row| row.to_vec()).collect())
+        .collect();
+
+    let mut too_short = raw.clone();
+    too_short[1][2].pop();
+    assert!(PublicKey::<M>::new(too_short).is_err());
+    let mut out_of_range = raw.clone();
+    out_of_range[5][6][7] = m;
+    assert!(PublicKey::<M>::new(out_of_range).is_err());
+
+    let rebuilt = PublicKey::<M>::new(raw).unwrap();
+    for ie in 0..8 {
+        for ix in 0..8 {
+            for iy in 0..8 {
+                assert_eq!(rebuilt[(ie, ix, iy)], pk[(ie, ix, iy)]);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_decrypt_malformed_secret_key() {
+    let schema = Schema::<M>::new();
+    let (mut sk, pk) = schema.gen_sk_pk();
+    let ct = schema.encrypt(schema.new_plaintext(BigInt::from(3)), &pk);
+    sk.a[1] = Octonion::zero();
+    assert!(matches!(
+        schema.decrypt(ct, &sk),
+        Err(OctonionError::MalformedKey(_))
+    ));
+    assert!(PublicKey::new_from_sk(&sk).is_err());
+}
+
+#[test]
+fn test_try_new() {
+    assert!(Schema::<M>::try_new().is_ok());
+    // every odd prime has a schema, p = 5 has the fewest successful tries
+    for _ in 0..20 {
+        assert!(Schema::<"3">::try_new().is_ok());
+        assert!(Schema::<"5">::try_new().is_ok());
+        assert!(Schema::<"7">::try_new().is_ok());
+        assert!(Schema::<"13">::try_new().is_ok());
+    }
+
+    // find_g_h can never succeed without 1/2 or a field, rejected before any search
+    fn impossible<const MOD: &'static str>() -> bool {
+        matches!(
+            Schema::<MOD>::try_new(),
+            Err(OctonionError::InvalidModulus { .. })
+        )
+    }
+    assert!(impossible::<"2">());
+    assert!(impossible::<"9">());
+    assert!(impossible::<"15">());
+    assert!(impossible::<"561">());
+    assert!(impossible::<"-5">());
+    assert!(impossible::<"five">());
+}
+
+#[test]
+fn test_find_g_h_bounded() {
+    let m = BigInt::from_str(M).unwrap();
+    let solution = Schema::<M>::find_g_h_bounded(1000).unwrap();
+    let g = solution.g;
+    assert_eq!(g.clone() * g.clone(), g);
+    for h in [solution.hs.0, solution.hs.1].iter() {
+        assert_eq!(h.a0, BigInt::from(0));
+        assert!((h.clone() * h.clone()).is_zero());
+        assert_eq!((g.clone() * h.clone()).a0 % &m, BigInt::from(0));
+    }
+    assert_eq!(solution.stats.attempts, solution.stats.rejected() + 1);
+}
+
+#[test]
+fn test_find_g_h_bounded_exhausted() {
+    assert_eq!(
+        Schema::<M>::find_g_h_bounded(0).unwrap_err(),
+        OctonionError::SearchExhausted {
+            attempts: 0,
+            reason: GHSearchStats::default().to_string(),
+            stats: Some(GHSearchStats::default()),
+        }
+    );
+    // with p = 3 most tries fail, the stats come back with the error
+    match Schema::<"3">::find_g_h_bounded(1) {
+        Ok(solution) => assert_eq!(solution.stats.attempts, 1),
+        Err(OctonionError::SearchExhausted {
+            attempts,
+            stats: Some(stats),
+            ..
+        }) => {
+            assert_eq!(attempts, 1);
+            assert_eq!(stats.attempts, 1);
+            assert_eq!(stats.rejected(), 1);
+        }
+        Err(e) => panic!("unexpected error {}", e),
+    }
+}
+
+#[test]
+fn test_find_g_h_g6_zero() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    // g6 is 0 in a third of the tries mod 3, each of them must be counted as G6Zero
+    let mut rng = StdRng::seed_from_u64(34);
+    let mut stats = GHSearchStats::default();
+    for _ in 0..300 {
+        stats.attempts += 1;
+        if let Err(rejection) = Schema::<"3">::try_find_g_h(&mut rng) {
+            stats.record(rejection);
+        }
+    }
+    assert!(stats.g6_zero > 50, "{}", stats);
+
+    // and no accepted g has g6 = 0
+    for _ in 0..100 {
+        if let Ok((g, _)) = Schema::<"3">::try_find_g_h(&mut rng) {
+            assert_ne!(g[6], BigInt::from(0));
+        }
+    }
+}
+
+#[test]
+fn test_sample_g_h() {
+    let m = BigInt::from_str(M).unwrap();
+    for _ in 0..4 {
+        let (g, h) = Schema::<M>::sample_g_h(100).unwrap();
+        assert_eq!(g.clone() * g.clone(), g);
+        assert!((h.clone() * h.clone()).is_zero());
+        assert!(!h.is_zero());
+        assert_eq!((g.clone() * h.clone()).a0 % &m, BigInt::from(0));
+    }
+}
+
+#[test]
+fn test_solve_g_h() {
+    // the slice find_g_h samples: g7, h6 and h7 unknown
+    let solution = Schema::<M>::find_g_h_bounded(1000).unwrap();
+    let (g, h) = (solution.g, solution.hs.0);
+    let mut fixed: Vec<(usize, BigInt)> = (1..7).map(|i| (i, g[i].clone())).collect();
+    fixed.extend((1..6).map(|i| (8 + i, h[i].clone())));
+    let solutions = Schema::<M>::solve_g_h(&fixed).unwrap();
+    assert!(solutions.iter().any(|s| s == &(g.clone(), h.clone())));
+    for (g, h) in solutions {
+        assert_eq!(g.clone() * g.clone(), g);
+        assert!((h.clone() * h).is_zero());
+    }
+}
+
+#[test]
+fn test_verify() {
+    let m = BigInt::from_str(M).unwrap();
+    let schema = Schema::<M>::new();
+    assert!(schema.verify().is_ok());
+    let (g, h) = (schema.g().clone(), schema.h().clone());
+    assert!(Schema::try_new_with_g_h(g.clone(), h.clone()).is_ok());
+
+    let invalid = |g: Octonion<M>, h: Octonion<M>| {
+        matches!(
+            Schema::try_new_with_g_h(g, h),
+            Err(OctonionError::InvalidSchema(_))
+        )
+    };
+    // g and h swapped
+    assert!(invalid(h.clone(), g.clone()));
+    assert!(invalid(g.clone(), Octonion::zero()));
+    assert!(invalid(Octonion::one(), h.clone()));
+    // h = g - 1/2 has real part 0, but N(h) = -1/4
+    let mut h_bad = g.clone();
+    h_bad.a0 = BigInt::from(0);
+    assert!(invalid(g.clone(), h_bad));
+    // N(g + 1 e1) != 0
+    let mut g_bad = g.clone();
+    g_bad.a1 = (&g_bad.a1 + 1) % &m;
+    assert!(invalid(g_bad, h.clone()));
+
+    let mut g_out_of_range = g.clone();
+    g_out_of_range.a1 += &m;
+    assert!(matches!(
+        Schema::try_new_with_g_h(g_out_of_range, h),
+        Err(OctonionError::ModulusMismatch { .. })
+    ));
+}
+
+#[quickcheck]
+fn test_decompose(a: PlainText<M>, b: PlainText<M>) -> bool {
+    let m = BigInt::from_str(M).unwrap();
+    let schema = Schema::new();
+    let am = schema.p_to_m(a.clone());
+    let bm = schema.p_to_m(b.clone());
+    let (p, u, v) = am.decompose(&schema).unwrap();
+    let rebuilt = &p * schema.g().clone()
+        + &u * schema.h().clone()
+        + &v * (schema.g().clone() * schema.h().clone());
+
+    // products and sums stay in the span
+    let prod = MediamText {
+        value: am.value.clone() * bm.value.clone(),
+    };
+    let sum = MediamText {
+        value: am.value.clone() + bm.value,
+    };
+    let (prod_p, _, _) = prod.decompose(&schema).unwrap();
+    let (sum_p, _, _) = sum.decompose(&schema).unwrap();
+
+    p == a.value
+        && rebuilt == am.value
+        && prod_p == (&a.value * &b.value) % &m
+        && sum_p == (&a.value + &b.value) % &m
+}
+
+#[test]
+fn test_decompose_known() {
+    let m = BigInt::from_str(M).unwrap();
+    let schema = Schema::<M>::new();
+    let (g, h) = (schema.g().clone(), schema.h().clone());
+    let (p, u, v, w) = (
+        BigInt::from(3),
+        BigInt::from(5),
+        BigInt::from(7),
+        BigInt::from(11),
+    );
+    let value = &p * g.clone()
+        + &u * h.clone()
+        + &v * (g.clone() * h.clone())
+        + &w * (h.clone() * g.clone());
+    // hg = h - gh, so w moves onto h and gh
+    assert_eq!(
+        MediamText { value }.decompose(&schema).unwrap(),
+        (p, &u + &w, (&v - &w + &m) % &m)
+    );
+}
+
+#[test]
+fn test_is_well_formed() {
+    let schema = Schema::<M>::new();
+    assert!(!MediamText {
+        value: Octonion::one()
+    }
+    .is_well_formed(&schema));
+    assert!(MediamText {
+        value: Octonion::zero()
+    }
+    .is_well_formed(&schema));
+    assert!(MediamText {
+        value: schema.g().clone()
+    }
+    .is_well_formed(&schema));
+}
+
+#[quickcheck]
+fn test_decrypt_checked(pt: PlainText<M>) -> bool {
+    let schema = Schema::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let ct = schema.encrypt(pt.clone(), &pk);
+
+    let mut corrupted = ct
+        .coefficients()
+        .iter()
+        .map(|row| row.to_vec())
+        .collect::<Vec<_>>();
+    corrupted[3][5] = (&corrupted[3][5] + 1) % BigInt::from_str(M).unwrap();
+    let corrupted = CipherText::new(corrupted).unwrap();
+
+    schema.decrypt_checked(ct, &sk).unwrap().value == pt.value
+        && schema.decrypt_checked(corrupted, &sk).is_err()
+}
+
+#[quickcheck]
+fn test_mul_scalar(pt: PlainText<M>, k: PlainText<M>) -> bool {
+    let m = BigInt::from_str(M).unwrap();
+    let schema = Schema::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let ct = schema.encrypt(pt.clone(), &pk);
+    let ans = (&pt.value * &k.value) % &m;
+    schema.decrypt(ct.mul_scalar(&k.value), &sk).unwrap().value == ans
+        && schema.decrypt(ct.mul_plain(&k), &sk).unwrap().value == ans
+}
+
+#[quickcheck]
+fn test_add_plain(pt: PlainText<M>, c: PlainText<M>, rhs: PlainText<M>) -> bool {
+    let m = BigInt::from_str(M).unwrap();
+    let schema = Schema::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let unit = schema.unit(&pk);
+    let ct = schema.encrypt(pt.clone(), &pk);
+    let rhs_ct = schema.encrypt(rhs.clone(), &pk);
+
+    let sum = ct.add_plain(&c, &unit);
+    let sum_pt = (&pt.value + &c.value) % &m;
+    // the sum still multiplies correctly
+    let prod = &sum * &rhs_ct;
+    schema.decrypt(unit, &sk).unwrap().value == BigInt::from(1)
+        && schema.decrypt(sum.clone(), &sk).unwrap().value == sum_pt
+        && schema.decrypt(prod, &sk).unwrap().value == (&sum_pt * &rhs.value) % &m
+}
+
+#[quickcheck]
+fn test_encrypt_decrypt_neg(pt: PlainText<M>) -> bool {
+    let m = BigInt::from_str(M).unwrap();
+    let schema = Schema::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let ct = schema.encrypt(pt.clone(), &pk);
+    let ans = (&m - &pt.value) % &m;
+    schema.decrypt(-&ct, &sk).unwrap().value == ans
+        && schema.decrypt(-ct, &sk).unwrap().value == ans
+}
+
+#[quickcheck]
+fn test_encrypt_decrypt_sub(lhs_pt: PlainText<M>, rhs_pt: PlainText<M>) -> bool {
+    let m = BigInt::from_str(M).unwrap();
+    let schema = Schema::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let lhs_ct = schema.encrypt(lhs_pt.clone(), &pk);
+    let rhs_ct = schema.encrypt(rhs_pt.clone(), &pk);
+    let ans = (&m + &lhs_pt.value - &rhs_pt.value) % &m;
+
+    let sub_ref = schema.decrypt(&lhs_ct - &rhs_ct, &sk).unwrap();
+    // (a - b) * b still decrypts, the difference stays a valid ciphertext
+    let prod = schema.decrypt(&(&lhs_ct - &rhs_ct) * &rhs_ct, &sk).unwrap();
+    let sub_owned = schema.decrypt(lhs_ct - rhs_ct, &sk).unwrap();
+    sub_ref.value == ans && sub_owned.value == ans && prod.value == (&ans * &rhs_pt.value) % &m
+}
+
+#[quickcheck]
+fn test_pow(pt: PlainText<M>, e: u8) -> bool {
+    let m = BigInt::from_str(M).unwrap();
+    let e = e as u64 % 21;
+    let schema = Schema::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let ct = schema.encrypt(pt.clone(), &pk);
+    let unit = schema.unit(&pk);
+    schema.decrypt(ct.pow(e, &unit), &sk).unwrap().value == pt.value.modpow(&BigInt::from(e), &m)
+}
+
+#[quickcheck]
+fn test_eval_poly(pt: PlainText<M>, coeffs: Vec<i32>) -> bool {
+    let m = BigInt::from_str(M).unwrap();
+    let coeffs: Vec<BigInt> = coeffs.iter().take(6).map(|c| BigInt::from(*c)).collect();
+    let schema = Schema::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let unit = schema.unit(&pk);
+    let ct = schema.encrypt(pt.clone(), &pk);
+
+    let mut ans = BigInt::from(0);
+    for c in coeffs.iter().rev() {
+        ans = (ans * &pt.value + c) % &m;
+    }
+    if ans < BigInt::from(0) {
+        ans += &m;
+    }
+    schema
+        .decrypt(ct.eval_poly(&coeffs, &unit), &sk)
+        .unwrap()
+        .value
+        == ans
+}
+
+#[quickcheck]
+fn test_eval_polynomial(x: PlainText<M>, y: PlainText<M>) -> bool {
+    use crate::crypto::eval::eval_polynomial;
+    use crate::polynomial::Polynomial;
+
+    let schema = Schema::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let unit = schema.unit(&pk);
+    let inputs = vec![
+        schema.encrypt(x.clone(), &pk),
+        schema.encrypt(y.clone(), &pk),
+    ];
+
+    // 3 x^4 y - x^2 + 5 y^3 + 7
+    let poly = Polynomial::<M>::from_terms(
+        2,
+        vec![
+            (vec![4, 1], BigInt::from(3)),
+            (vec![2, 0], BigInt::from(-1)),
+            (vec![0, 3], BigInt::from(5)),
+            (vec![0, 0], BigInt::from(7)),
+        ],
+    )
+    .unwrap();
+    let ct = eval_polynomial(&poly, &inputs, &unit).unwrap();
+    let ans = poly.eval(&[x.value, y.value]);
+    schema.decrypt(ct, &sk).unwrap().value == ans
+        && eval_polynomial(&poly, &inputs[..1], &unit).is_err()
+}
+
+#[test]
+fn test_power_cache() {
+    use crate::crypto::eval::PowerCache;
+
+    let schema = Schema::<M>::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let inputs = vec![schema.encrypt(schema.new_plaintext(BigInt::from(3)), &pk)];
+    let unit = schema.unit(&pk);
+    let mut cache = PowerCache::new(&inputs, &unit);
+    // x^2, x^4, x^8
+    let x8 = cache.pow(0, 8);
+    assert_eq!(cache.multiplications(), 3);
+    // x^3 reuses x^2, x^6 reuses x^3
+    let _ = cache.pow(0, 6);
+    assert_eq!(cache.multiplications(), 5);
+    let _ = cache.pow(0, 4);
+    assert_eq!(cache.multiplications(), 5);
+    let x0 = cache.pow(0, 0);
+    assert_eq!(cache.multiplications(), 5);
+    assert_eq!(schema.decrypt(x0, &sk).unwrap().value, BigInt::from(1));
+    assert_eq!(
+        schema.decrypt(x8, &sk).unwrap().value,
+        BigInt::from(3u32.pow(8))
+    );
+}
+
+fn encrypt_bits(schema: &Schema<M>, pk: &PublicKey<M>, x: u8) -> Vec<CipherText<M>> {
+    (0..8)
+        .map(|i| schema.encrypt(schema.new_plaintext(BigInt::from((x >> i) & 1)), pk))
+        .collect()
+}
+
+fn decrypt_bits(schema: &Schema<M>, sk: &SecretKey<M>, bits: Vec<CipherText<M>>) -> u64 {
+    let mut x = 0;
+    for (i, ct) in bits.into_iter().enumerate() {
+        let bit = schema.decrypt(ct, sk).unwrap().value;
+        assert!(bit == BigInt::from(0) || bit == BigInt::from(1));
+        if bit == BigInt::from(1) {
+            x |= 1 << i;
+        }
+    }
+    x
+}
+
+#[test]
+fn test_boolean_gates() {
+    use crate::crypto::boolean::BooleanGates;
+
+    let schema = Schema::<M>::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let gates = BooleanGates::new(schema.unit(&pk));
+    let bit = |ct: CipherText<M>| schema.decrypt(ct, &sk).unwrap().value == BigInt::from(1);
+    for a in [false, true].iter() {
+        for b in [false, true].iter() {
+            let x = schema.encrypt(schema.new_plaintext(BigInt::from(*a as u8)), &pk);
+            let y = schema.encrypt(schema.new_plaintext(BigInt::from(*b as u8)), &pk);
+            assert_eq!(bit(gates.not(&x)), !a);
+            assert_eq!(bit(gates.and(&x, &y)), a & b);
+            assert_eq!(bit(gates.or(&x, &y)), a | b);
+            assert_eq!(bit(gates.xor(&x, &y)), a ^ b);
+            assert_eq!(bit(gates.nand(&x, &y)), !(a & b));
+            assert_eq!(bit(gates.xnor(&x, &y)), !(a ^ b));
+            for s in [false, true].iter() {
+                let sel = gates.constant(*s);
+                assert_eq!(bit(gates.mux(&sel, &x, &y)), if *s { *a } else { *b });
+                let (sum, carry) = gates.full_adder(&x, &y, &sel);
+                let total = *a as u8 + *b as u8 + *s as u8;
+                assert_eq!(bit(sum), total & 1 == 1);
+                assert_eq!(bit(carry), total >= 2);
+            }
+        }
+    }
+}
+
+#[quickcheck]
+fn test_boolean_arithmetic(a: u8, b: u8, sel: bool) -> bool {
+    use crate::crypto::boolean::BooleanGates;
+
+    let schema = Schema::<M>::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let gates = BooleanGates::new(schema.unit(&pk));
+    let x = encrypt_bits(&schema, &pk, a);
+    let y = encrypt_bits(&schema, &pk, b);
+    let flag = |ct: CipherText<M>| decrypt_bits(&schema, &sk, vec![ct]) == 1;
+    let s = gates.constant(sel);
+
+    decrypt_bits(&schema, &sk, gates.add(&x, &y).unwrap()) == a as u64 + b as u64
+        && decrypt_bits(&schema, &sk, gates.sub(&x, &y).unwrap()) == a.wrapping_sub(b) as u64
+        && flag(gates.eq(&x, &y).unwrap()) == (a == b)
+        && flag(gates.eq(&x, &x).unwrap())
+        && flag(gates.lt(&x, &y).unwrap()) == (a < b)
+        && flag(gates.le(&x, &y).unwrap()) == (a <= b)
+        && decrypt_bits(&schema, &sk, gates.min(&x, &y).unwrap()) == a.min(b) as u64
+        && decrypt_bits(&schema, &sk, gates.max(&x, &y).unwrap()) == a.max(b) as u64
+        && decrypt_bits(&schema, &sk, gates.mux_bits(&s, &x, &y).unwrap())
+            == if sel { a } else { b } as u64
+        && gates.add(&x, &y[..7]).is_err()
+}
+
+fn random_vector(n: usize) -> Vector<M> {
+    let m = BigInt::from_str(M).unwrap();
+    let mut rng = rand::thread_rng();
+    Vector::new(
+        (0..n)
+            .map(|_| rng.gen_bigint_range(&BigInt::from(0), &m))
+            .collect(),
+    )
+}
+
+#[test]
+fn test_tree_sum() {
+    use crate::crypto::vector::tree_sum;
+
+    let schema = Schema::<M>::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    assert_eq!(
+        schema.decrypt(tree_sum(vec![]), &sk).unwrap().value,
+        BigInt::from(0)
+    );
+    for n in 1..8u32 {
+        let cts = (1..=n)
+            .map(|i| schema.encrypt(schema.new_plaintext(BigInt::from(i)), &pk))
+            .collect();
+        assert_eq!(
+            schema.decrypt(tree_sum(cts), &sk).unwrap().value,
+            BigInt::from(n * (n + 1) / 2)
+        );
+    }
+}
+
+#[test]
+fn test_encrypted_vector() {
+    use crate::crypto::vector::EncryptedVector;
+
+    let m = BigInt::from_str(M).unwrap();
+    let schema = Schema::<M>::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let (x, y) = (random_vector(5), random_vector(5));
+    let ex = EncryptedVector::encrypt(&schema, &x, &pk);
+    let ey = EncryptedVector::encrypt(&schema, &y, &pk);
+    let k = BigInt::from(7);
+
+    assert_eq!(ex.decrypt(&schema, &sk).unwrap(), x);
+    assert_eq!((&ex + &ey).decrypt(&schema, &sk).unwrap(), &x + &y);
+    assert_eq!((&ex - &ey).decrypt(&schema, &sk).unwrap(), &x - &y);
+    assert_eq!(
+        (-&ex).decrypt(&schema, &sk).unwrap(),
+        &Vector::zeros(5) - &x
+    );
+    assert_eq!(ex.scale(&k).decrypt(&schema, &sk).unwrap(), x.scale(&k));
+
+    let hadamard = Vector::new(x.iter().zip(y.iter()).map(|(a, b)| a * b % &m).collect());
+    assert_eq!(
+        ex.hadamard(&ey).unwrap().decrypt(&schema, &sk).unwrap(),
+        hadamard
+    );
+    assert_eq!(
+        ex.mul_plain(&y).unwrap().decrypt(&schema, &sk).unwrap(),
+        hadamard
+    );
+
+    let sum = x.iter().fold(BigInt::from(0), |acc, a| (acc + a) % &m);
+    assert_eq!(schema.decrypt(ex.sum(), &sk).unwrap().value, sum);
+    assert_eq!(
+        schema.decrypt(ex.dot(&ey).unwrap(), &sk).unwrap().value,
+        x.dot(&y)
+    );
+    assert_eq!(
+        schema
+            .decrypt(ex.dot_plain(&y).unwrap(), &sk)
+            .unwrap()
+            .value,
+        x.dot(&y)
+    );
+    assert!(ex.dot(&EncryptedVector::new(vec![])).is_err());
+    assert!(ex.dot_plain(&random_vector(4)).is_err());
+}
+
+#[test]
+fn test_encrypted_matrix() {
+    use crate::crypto::vector::{EncryptedMatrix, EncryptedVector};
+
+    let schema = Schema::<M>::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let a = Matrix::new(2, 3, random_vector(6).into_vec()).unwrap();
+    let x = random_vector(3);
+    let ea = EncryptedMatrix::encrypt(&schema, &a, &pk);
+    let ex = EncryptedVector::encrypt(&schema, &x, &pk);
+
+    assert_eq!(ea.decrypt(&schema, &sk).unwrap(), a);
+    assert_eq!((&ea * &x).decrypt(&schema, &sk).unwrap(), &a * &x);
+    assert_eq!((&ea * &ex).decrypt(&schema, &sk).unwrap(), &a * &x);
+    assert_eq!((&a * &ex).decrypt(&schema, &sk).unwrap(), &a * &x);
+    assert!(EncryptedMatrix::new(2, 2, ex.into_vec()).is_err());
+}
+
+#[test]
+fn test_encrypted_matrix_elementwise() {
+    use crate::crypto::vector::EncryptedMatrix;
+
+    let m = BigInt::from_str(M).unwrap();
+    let schema = Schema::<M>::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let a = Matrix::new(2, 3, random_vector(6).into_vec()).unwrap();
+    let b = Matrix::new(2, 3, random_vector(6).into_vec()).unwrap();
+    let ea = EncryptedMatrix::encrypt(&schema, &a, &pk);
+    let eb = EncryptedMatrix::encrypt(&schema, &b, &pk);
+    let k = BigInt::from(7);
+
+    assert_eq!((&ea + &eb).decrypt(&schema, &sk).unwrap(), &a + &b);
+    assert_eq!((&ea - &eb).decrypt(&schema, &sk).unwrap(), &a - &b);
+    assert_eq!((-&ea).decrypt(&schema, &sk).unwrap(), -&a);
+    assert_eq!(ea.scale(&k).decrypt(&schema, &sk).unwrap(), a.scale(&k));
+
+    let hadamard = Matrix::new(
+        2,
+        3,
+        a.clone()
+            .into_vec()
+            .iter()
+            .zip(b.clone().into_vec().iter())
+            .map(|(x, y)| x * y % &m)
+            .collect(),
+    )
+    .unwrap();
+    assert_eq!(
+        ea.hadamard(&eb).unwrap().decrypt(&schema, &sk).unwrap(),
+        hadamard
+    );
+    assert_eq!(
+        ea.mul_plain(&b).unwrap().decrypt(&schema, &sk).unwrap(),
+        hadamard
+    );
+
+    let at = a.transpose();
+    assert!(ea.mul_plain(&at).is_err());
+    assert!(ea
+        .hadamard(&EncryptedMatrix::encrypt(&schema, &at, &pk))
+        .is_err());
+}
+
+#[test]
+#[should_panic]
+fn test_encrypted_matrix_add_mismatch() {
+    use crate::crypto::vector::EncryptedMatrix;
+
+    let schema = Schema::<M>::new();
+    let (_, pk) = schema.gen_sk_pk();
+    let a = Matrix::new(2, 3, random_vector(6).into_vec()).unwrap();
+    let _ = &EncryptedMatrix::encrypt(&schema, &a, &pk)
+        + &EncryptedMatrix::encrypt(&schema, &a.transpose(), &pk);
+}
+
+#[quickcheck]
+fn test_aggregator(xs: Vec<i16>) -> bool {
+    use crate::crypto::aggregate::Aggregator;
+    use crate::encoding::signed;
+
+    let xs: Vec<i64> = xs.into_iter().take(20).map(|x| x as i64).collect();
+    let schema = Schema::<M>::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let cts: Vec<_> = xs
+        .iter()
+        .map(|x| schema.encrypt(signed::encode(&BigInt::from(*x)).unwrap(), &pk))
+        .collect();
+
+    // two workers, merged
+    let (left, right) = cts.split_at(cts.len() / 2);
+    let mut agg = Aggregator::new(&schema, &pk);
+    agg.extend(left.iter().cloned());
+    let mut other = Aggregator::new(&schema, &pk);
+    for ct in right {
+        other.push(ct);
+    }
+    agg.merge(&other);
+    let stats = agg.decrypt(&schema, &sk).unwrap();
+
+    let n = xs.len() as i64;
+    let sum: i64 = xs.iter().sum();
+    let sum_of_squares: i64 = xs.iter().map(|x| x * x).sum();
+    let close = |a: Option<f64>, b: f64| (a.unwrap() - b).abs() <= 1e-9 * b.abs().max(1.0);
+    if agg.len() != n as u64
+        || stats.count != BigInt::from(n)
+        || stats.sum != BigInt::from(sum)
+        || stats.sum_of_squares != BigInt::from(sum_of_squares)
+    {
+        return false;
+    }
+    if n == 0 {
+        return stats.mean().is_none() && stats.variance().is_none();
+    }
+    let mean = sum as f64 / n as f64;
+    let variance = xs.iter().map(|x| (*x as f64 - mean).powi(2)).sum::<f64>() / n as f64;
+    close(stats.mean(), mean)
+        && close(stats.variance(), variance)
+        && (n < 2
+            || close(
+                stats.sample_variance(),
+                variance * n as f64 / (n - 1) as f64,
+            ))
+}
+
+#[test]
+fn test_encrypt_decrypt_batch() {
+    let schema = Schema::<M>::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let pts: Vec<_> = random_vector(10)
+        .into_vec()
+        .into_iter()
+        .map(|x| schema.new_plaintext(x))
+        .collect();
+    let values: Vec<_> = pts.iter().map(|pt| pt.value.clone()).collect();
+    let cts = schema.encrypt_batch(pts, &pk);
+    let sequential: Vec<_> = cts
+        .iter()
+        .map(|ct| schema.decrypt(ct.clone(), &sk).unwrap().value)
+        .collect();
+    let batch: Vec<_> = schema
+        .decrypt_batch(cts, &sk)
+        .unwrap()
+        .into_iter()
+        .map(|pt| pt.value)
+        .collect();
+    assert_eq!(batch, values);
+    assert_eq!(sequential, values);
+}
+
+#[test]
+fn test_tree_product() {
+    use crate::crypto::vector::{tree_product, tree_sum};
+
+    let m = BigInt::from_str(M).unwrap();
+    let schema = Schema::<M>::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let xs = random_vector(9);
+    let cts: Vec<_> = xs
+        .iter()
+        .map(|x| schema.encrypt(schema.new_plaintext(x.clone()), &pk))
+        .collect();
+
+    // the same matrices as a left fold, not just the same plaintexts
+    let fold_sum = cts.iter().skip(1).fold(cts[0].clone(), |acc, ct| &acc + ct);
+    let fold_product = cts.iter().skip(1).fold(cts[0].clone(), |acc, ct| &acc * ct);
+    let sum: CipherText<M> = cts.iter().sum();
+    let product = tree_product(cts.clone()).unwrap();
+    assert_eq!(sum.coefficients(), fold_sum.coefficients());
+    assert_eq!(tree_sum(cts).coefficients(), fold_sum.coefficients());
+    assert_eq!(product.coefficients(), fold_product.coefficients());
+    assert!(tree_product::<M>(vec![]).is_none());
+
+    let ans = xs.iter().fold(BigInt::from(1), |acc, x| acc * x % &m);
+    assert_eq!(schema.decrypt(product, &sk).unwrap().value, ans);
+}
+
+#[test]
+fn test_gen_sk_pk_with_rng() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let schema = Schema::<M>::new();
+    let (sk, pk) = schema.gen_sk_pk_with_rng(&mut StdRng::seed_from_u64(49));
+    let (sk2, pk2) = schema.gen_sk_pk_with_rng(&mut StdRng::seed_from_u64(49));
+    assert_eq!(sk.a, sk2.a);
+    assert_eq!(pk.coefficients(), pk2.coefficients());
+
+    // one basis pair at a time, as without the parallel feature
+    for ix in 0..8 {
+        for iy in 0..8 {
+            let mut x = Octonion::<M>::zero();
+            let mut y = Octonion::<M>::zero();
+            x[ix] = BigInt::from(1);
+            y[iy] = BigInt::from(1);
+            for a in sk.a.iter() {
+                x = a.inverse().unwrap() * x;
+            }
+            let mut ans = y * x;
+            for a in sk.a.iter().rev() {
+                ans = a.clone() * ans;
+            }
+            for ie in 0..8 {
+                assert_eq!(pk[(ie, ix, iy)], ans[ie]);
+            }
+        }
+    }
+
+    let pt = schema.new_plaintext(BigInt::from(42));
+    let ct = schema.encrypt(pt.clone(), &pk);
+    assert_eq!(schema.decrypt(ct, &sk).unwrap().value, pt.value);
+}
+
+#[test]
+fn test_encrypt_with_rng() {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    let schema = Schema::<M>::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let pt = schema.new_plaintext(BigInt::from(50));
+    let ct = schema.encrypt_with_rng(pt.clone(), &pk, &mut StdRng::seed_from_u64(50));
+    let ct2 = schema.encrypt_with_rng(pt.clone(), &pk, &mut StdRng::seed_from_u64(50));
+    assert_eq!(ct.coefficients(), ct2.coefficients());
+    assert_eq!(schema.decrypt(ct, &sk).unwrap().value, pt.value);
+}
+
+#[quickcheck]
+fn test_rerandomize(pt: PlainText<M>, rhs: PlainText<M>) -> bool {
+    let m = BigInt::from_str(M).unwrap();
+    let schema = Schema::new();
+    let (sk, pk) = schema.gen_sk_pk();
+    let mut rng = rand::thread_rng();
+    let ct = schema.encrypt(pt.clone(), &pk);
+    let rhs_ct = schema.encrypt(rhs.clone(), &pk);
+
+    let fresh = ct.rerandomize(&pk, &schema, &mut rng);
+    let again = ct.rerandomize(&pk, &schema, &mut rng);
+    // still a valid ciphertext, products decrypt too
+    let prod = &fresh * &rhs_ct;
+    fresh.coefficients() != ct.coefficients()
+        && fresh.coefficients() != again.coefficients()
+        && schema.decrypt_checked(fresh, &sk).unwrap().value == pt.value
+        && schema.decrypt(again, &sk).unwrap().value == pt.value
+        && schema.decrypt(prod, &sk).unwrap().value == (&pt.value * &rhs.value) % &m
+}
@@ -0,0 +1,448 @@
+//! vectors and matrices of ciphertexts, mirroring linalg::Vector and linalg::Matrix.
+use std::ops::{Add, Index, Mul, Neg, Sub};
+
+use num_bigint::BigInt;
+
+use crate::crypto::cipher_text::CipherText;
+use crate::crypto::{PlainText, PublicKey, Schema, SecretKey};
+use crate::error::{OctonionError, Result};
+use crate::linalg::{Matrix, Vector};
+
+/// vector of ciphertexts
+#[derive(Debug, Clone)]
+pub struct EncryptedVector<const MOD: &'static str> {
+    data: Vec<CipherText<MOD>>,
+}
+
+/// matrix of ciphertexts, row major
+#[derive(Debug, Clone)]
+pub struct EncryptedMatrix<const MOD: &'static str> {
+    rows: usize,
+    cols: usize,
+    data: Vec<CipherText<MOD>>,
+}
+
+/// a op b op ... for an associative op, paired up in a balanced tree.
+/// with the parallel feature the pairs are spread over all cores, the result is the same
+fn tree_reduce<const MOD: &'static str, F>(
+    cts: Vec<CipherText<MOD>>,
+    op: F,
+) -> Option<CipherText<MOD>>
+where
+    F: Fn(&CipherText<MOD>, &CipherText<MOD>) -> CipherText<MOD> + Sync + Send,
+{
+    #[cfg(feature = "parallel")]
+    {
+        use rayon::prelude::*;
+        cts.into_par_iter().reduce_with(|a, b| op(&a, &b))
+    }
+    #[cfg(not(feature = "parallel"))]
+    {
+        let mut level = cts;
+        while level.len() > 1 {
+            let mut next = Vec::with_capacity(level.len().div_ceil(2));
+            let mut iter = level.into_iter();
+            while let Some(a) = iter.next() {
+                next.push(match iter.next() {
+                    Some(b) => op(&a, &b),
+                    None => a,
+                });
+            }
+            level = next;
+        }
+        level.pop()
+    }
+}
+
+/// sum of cts, added pairwise in a balanced tree. the zero ciphertext if cts is empty
+pub fn tree_sum<const MOD: &'static str>(cts: Vec<CipherText<MOD>>) -> CipherText<MOD> {
+    tree_reduce(cts, |a, b| a + b).unwrap_or_else(CipherText::zero)
+}
+
+/// product of cts in order, multiplied pairwise in a balanced tree.
+/// None if cts is empty, the empty product is Schema::unit
+pub fn tree_product<const MOD: &'static str>(cts: Vec<CipherText<MOD>>) -> Option<CipherText<MOD>> {
+    tree_reduce(cts, |a, b| a * b)
+}
+
+impl<const MOD: &'static str> EncryptedVector<MOD> {
+    pub fn new(data: Vec<CipherText<MOD>>) -> Self {
+        Self { data }
+    }
+
+    /// encrypt every entry of v
+    pub fn encrypt(schema: &Schema<MOD>, v: &Vector<MOD>, pk: &PublicKey<MOD>) -> Self {
+        let pts = v.iter().map(|x| PlainText { value: x.clone() }).collect();
+        Self {
+            data: schema.encrypt_batch(pts, pk),
+        }
+    }
+
+    /// decrypt every entry
+    pub fn decrypt(&self, schema: &Schema<MOD>, sk: &SecretKey<MOD>) -> Result<Vector<MOD>> {
+        let pts = schema.decrypt_batch(self.data.clone(), sk)?;
+        Ok(Vector::new(pts.into_iter().map(|pt| pt.value).collect()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, CipherText<MOD>> {
+        self.data.iter()
+    }
+
+    pub fn into_vec(self) -> Vec<CipherText<MOD>> {
+        self.data
+    }
+
+    fn check_len(&self, len: usize) -> Result<()> {
+        if self.len() != len {
+            return Err(OctonionError::DimensionMismatch(format!(
+                "vectors of length {} and {}",
+                self.len(),
+                len
+            )));
+        }
+        Ok(())
+    }
+
+    /// decrypts to k v
+    pub fn scale(&self, k: &BigInt) -> Self {
+        Self {
+            data: self.data.iter().map(|ct| ct.mul_scalar(k)).collect(),
+        }
+    }
+
+    /// elementwise product with a plaintext vector
+    pub fn mul_plain(&self, rhs: &Vector<MOD>) -> Result<Self> {
+        self.check_len(rhs.len())?;
+        Ok(Self {
+            data: self
+                .data
+                .iter()
+                .zip(rhs.iter())
+                .map(|(ct, x)| ct.mul_scalar(x))
+                .collect(),
+        })
+    }
+
+    /// elementwise product
+    pub fn hadamard(&self, rhs: &Self) -> Result<Self> {
+        self.check_len(rhs.len())?;
+        Ok(Self {
+            data: self
+                .data
+                .iter()
+                .zip(rhs.data.iter())
+                .map(|(a, b)| a * b)
+                .collect(),
+        })
+    }
+
+    /// sum of the entries
+    pub fn sum(&self) -> CipherText<MOD> {
+        tree_sum(self.data.clone())
+    }
+
+    /// inner product with a plaintext vector, no ciphertext multiplications
+    pub fn dot_plain(&self, rhs: &Vector<MOD>) -> Result<CipherText<MOD>> {
+        Ok(tree_sum(self.mul_plain(rhs)?.data))
+    }
+
+    /// inner product, one ciphertext multiplication per entry
+    pub fn dot(&self, rhs: &Self) -> Result<CipherText<MOD>> {
+        Ok(tree_sum(self.hadamard(rhs)?.data))
+    }
+}
+
+impl<const MOD: &'static str> EncryptedMatrix<MOD> {
+    /// build a rows x cols matrix from row major ciphertexts
+    pub fn new(rows: usize, cols: usize, data: Vec<CipherText<MOD>>) -> Result<Self> {
+        if data.len() != rows * cols {
+            return Err(OctonionError::DimensionMismatch(format!(
+                "{} x {} matrix needs {} entries, got {}",
+                rows,
+                cols,
+                rows * cols,
+                data.len()
+            )));
+        }
+        Ok(Self { rows, cols, data })
+    }
+
+    /// encrypt every entry of a
+    pub fn encrypt(schema: &Schema<MOD>, a: &Matrix<MOD>, pk: &PublicKey<MOD>) -> Self {
+        let pts = a
+            .clone()
+            .into_vec()
+            .into_iter()
+            .map(|x| PlainText { value: x })
+            .collect();
+        Self {
+            rows: a.rows(),
+            cols: a.cols(),
+            data: schema.encrypt_batch(pts, pk),
+        }
+    }
+
+    /// decrypt every entry
+    pub fn decrypt(&self, schema: &Schema<MOD>, sk: &SecretKey<MOD>) -> Result<Matrix<MOD>> {
+        let pts = schema.decrypt_batch(self.data.clone(), sk)?;
+        Matrix::new(
+            self.rows,
+            self.cols,
+            pts.into_iter().map(|pt| pt.value).collect(),
+        )
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn row(&self, i: usize) -> EncryptedVector<MOD> {
+        EncryptedVector::new(self.data[i * self.cols..(i + 1) * self.cols].to_vec())
+    }
+
+    /// row major entries
+    pub fn into_vec(self) -> Vec<CipherText<MOD>> {
+        self.data
+    }
+
+    fn check_shape(&self, rows: usize, cols: usize) -> Result<()> {
+        if self.rows != rows || self.cols != cols {
+            return Err(OctonionError::DimensionMismatch(format!(
+                "{} x {} and {} x {} matrices",
+                self.rows, self.cols, rows, cols
+            )));
+        }
+        Ok(())
+    }
+
+    /// decrypts to k a
+    pub fn scale(&self, k: &BigInt) -> Self {
+        Self {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(|ct| ct.mul_scalar(k)).collect(),
+        }
+    }
+
+    /// elementwise product with a plaintext matrix
+    pub fn mul_plain(&self, rhs: &Matrix<MOD>) -> Result<Self> {
+        self.check_shape(rhs.rows(), rhs.cols())?;
+        Ok(Self {
+            rows: self.rows,
+            cols: self.cols,
+            data: self
+                .data
+                .iter()
+                .zip(rhs.clone().into_vec().iter())
+                .map(|(ct, x)| ct.mul_scalar(x))
+                .collect(),
+        })
+    }
+
+    /// elementwise product
+    pub fn hadamard(&self, rhs: &Self) -> Result<Self> {
+        self.check_shape(rhs.rows, rhs.cols)?;
+        Ok(Self {
+            rows: self.rows,
+            cols: self.cols,
+            data: self
+                .data
+                .iter()
+                .zip(rhs.data.iter())
+                .map(|(a, b)| a * b)
+                .collect(),
+        })
+    }
+}
+
+impl<const MOD: &'static str> Index<usize> for EncryptedVector<MOD> {
+    type Output = CipherText<MOD>;
+    fn index(&self, i: usize) -> &CipherText<MOD> {
+        &self.data[i]
+    }
+}
+
+impl<const MOD: &'static str> Index<(usize, usize)> for EncryptedMatrix<MOD> {
+    type Output = CipherText<MOD>;
+    fn index(&self, (i, j): (usize, usize)) -> &CipherText<MOD> {
+        assert!(
+            i < self.rows && j < self.cols,
+            "index ({}, {}) out of range for {} x {} matrix",
+            i,
+            j,
+            self.rows,
+            self.cols
+        );
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl<const MOD: &'static str> Add<&EncryptedVector<MOD>> for &EncryptedVector<MOD> {
+    type Output = EncryptedVector<MOD>;
+    fn add(self, rhs: &EncryptedVector<MOD>) -> EncryptedVector<MOD> {
+        assert_eq!(self.len(), rhs.len(), "add of different lengths");
+        EncryptedVector {
+            data: self
+                .data
+                .iter()
+                .zip(rhs.data.iter())
+                .map(|(a, b)| a + b)
+                .collect(),
+        }
+    }
+}
+
+impl<const MOD: &'static str> Sub<&EncryptedVector<MOD>> for &EncryptedVector<MOD> {
+    type Output = EncryptedVector<MOD>;
+    fn sub(self, rhs: &EncryptedVector<MOD>) -> EncryptedVector<MOD> {
+        assert_eq!(self.len(), rhs.len(), "sub of different lengths");
+        EncryptedVector {
+            data: self
+                .data
+                .iter()
+                .zip(rhs.data.iter())
+                .map(|(a, b)| a - b)
+                .collect(),
+        }
+    }
+}
+
+impl<const MOD: &'static str> Neg for &EncryptedVector<MOD> {
+    type Output = EncryptedVector<MOD>;
+    fn neg(self) -> EncryptedVector<MOD> {
+        EncryptedVector {
+            data: self.data.iter().map(|a| -a).collect(),
+        }
+    }
+}
+
+impl<const MOD: &'static str> Add<&EncryptedMatrix<MOD>> for &EncryptedMatrix<MOD> {
+    type Output = EncryptedMatrix<MOD>;
+    fn add(self, rhs: &EncryptedMatrix<MOD>) -> EncryptedMatrix<MOD> {
+        assert!(
+            self.rows == rhs.rows && self.cols == rhs.cols,
+            "can't add {} x {} and {} x {} matrices",
+            self.rows,
+            self.cols,
+            rhs.rows,
+            rhs.cols
+        );
+        EncryptedMatrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self
+                .data
+                .iter()
+                .zip(rhs.data.iter())
+                .map(|(a, b)| a + b)
+                .collect(),
+        }
+    }
+}
+
+impl<const MOD: &'static str> Sub<&EncryptedMatrix<MOD>> for &EncryptedMatrix<MOD> {
+    type Output = EncryptedMatrix<MOD>;
+    fn sub(self, rhs: &EncryptedMatrix<MOD>) -> EncryptedMatrix<MOD> {
+        assert!(
+            self.rows == rhs.rows && self.cols == rhs.cols,
+            "can't sub {} x {} and {} x {} matrices",
+            self.rows,
+            self.cols,
+            rhs.rows,
+            rhs.cols
+        );
+        EncryptedMatrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self
+                .data
+                .iter()
+                .zip(rhs.data.iter())
+                .map(|(a, b)| a - b)
+                .collect(),
+        }
+    }
+}
+
+impl<const MOD: &'static str> Neg for &EncryptedMatrix<MOD> {
+    type Output = EncryptedMatrix<MOD>;
+    fn neg(self) -> EncryptedMatrix<MOD> {
+        EncryptedMatrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(|a| -a).collect(),
+        }
+    }
+}
+
+/// encrypted matrix times plaintext vector
+impl<const MOD: &'static str> Mul<&Vector<MOD>> for &EncryptedMatrix<MOD> {
+    type Output = EncryptedVector<MOD>;
+    fn mul(self, rhs: &Vector<MOD>) -> EncryptedVector<MOD> {
+        assert_eq!(
+            self.cols,
+            rhs.len(),
+            "can't multiply {} x {} matrix and vector of length {}",
+            self.rows,
+            self.cols,
+            rhs.len()
+        );
+        EncryptedVector {
+            data: (0..self.rows)
+                .map(|i| self.row(i).dot_plain(rhs).unwrap())
+                .collect(),
+        }
+    }
+}
+
+/// encrypted matrix times encrypted vector
+impl<const MOD: &'static str> Mul<&EncryptedVector<MOD>> for &EncryptedMatrix<MOD> {
+    type Output = EncryptedVector<MOD>;
+    fn mul(self, rhs: &EncryptedVector<MOD>) -> EncryptedVector<MOD> {
+        assert_eq!(
+            self.cols,
+            rhs.len(),
+            "can't multiply {} x {} matrix and vector of length {}",
+            self.rows,
+            self.cols,
+            rhs.len()
+        );
+        EncryptedVector {
+            data: (0..self.rows)
+                .map(|i| self.row(i).dot(rhs).unwrap())
+                .collect(),
+        }
+    }
+}
+
+/// plaintext matrix times encrypted vector
+impl<const MOD: &'static str> Mul<&EncryptedVector<MOD>> for &Matrix<MOD> {
+    type Output = EncryptedVector<MOD>;
+    fn mul(self, rhs: &EncryptedVector<MOD>) -> EncryptedVector<MOD> {
+        assert_eq!(
+            self.cols(),
+            rhs.len(),
+            "can't multiply {} x {} matrix and vector of length {}",
+            self.rows(),
+            self.cols(),
+            rhs.len()
+        );
+        EncryptedVector {
+            data: (0..self.rows())
+                .map(|i| rhs.dot_plain(&self.row(i)).unwrap())
+                .collect(),
+        }
+    }
+}
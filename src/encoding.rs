@@ -0,0 +1,8 @@
+//! encodings of application values into PlainText and back.
+//!
+//! a plaintext is an element of F_p, and homomorphic add and mul act on it mod p.
+//! each encoding maps its values to F_p so that these operations agree with the
+//! ones on the values, as long as results stay in the range the encoding can represent.
+pub mod fixed_point;
+pub mod message;
+pub mod signed;
@@ -0,0 +1,274 @@
+//! fixed point reals, x is encoded as the signed integer round(x * scale).
+//!
+//! a product of ciphertexts at scale^a and scale^b is at scale^(a + b), so every ciphertext
+//! carries its level. rounding costs at most 1 / (2 scale) per encoded value, and the
+//! level k result of a product of k values with |x| <= b is off by about k b^(k - 1) / (2 scale).
+//! the result must still fit the signed range, (b scale)^k <= (p - 1) / 2. with scale = 2^20
+//! and b = 2^9 that is 1 level for M31, 17 for M521 and 75 for M2203, see max_level.
+use std::ops::Mul;
+
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, ToPrimitive};
+
+use crate::crypto::cipher_text::CipherText;
+use crate::crypto::{PlainText, PublicKey, Schema, SecretKey};
+use crate::encoding::signed;
+use crate::error::{OctonionError, Result};
+
+/// fixed point encoder with a scale factor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedPoint<const MOD: &'static str> {
+    scale: BigInt,
+}
+
+/// ciphertext of x * scale^level
+#[derive(Debug, Clone)]
+pub struct ScaledCipherText<const MOD: &'static str> {
+    pub ct: CipherText<MOD>,
+    pub level: u32,
+}
+
+/// n / d as f64 without overflowing on huge n and d, None if the ratio itself is not finite
+fn ratio_to_f64(n: &BigInt, d: &BigInt) -> Option<f64> {
+    let shift = (d.bits().max(n.bits()) as usize).saturating_sub(1000);
+    let n = n >> shift;
+    let d = d >> shift;
+    Some(n.to_f64()? / d.to_f64()?).filter(|x| x.is_finite())
+}
+
+/// n / d rounded to the nearest integer, d > 0
+fn div_round(n: &BigInt, d: &BigInt) -> BigInt {
+    let num: BigInt = 2 * n + d;
+    let den: BigInt = 2 * d;
+    let mut q = &num / &den;
+    if &num % &den < BigInt::from(0) {
+        q -= 1;
+    }
+    q
+}
+
+impl<const MOD: &'static str> FixedPoint<MOD> {
+    /// scale must be in [1, (p - 1) / 2]
+    pub fn new(scale: BigInt) -> Result<Self> {
+        if scale < BigInt::from(1) || scale > signed::max_signed::<MOD>() {
+            return Err(OctonionError::InvalidParameter(format!(
+                "scale {} must be in [1, {}]",
+                scale,
+                signed::max_signed::<MOD>()
+            )));
+        }
+        Ok(Self { scale })
+    }
+
+    /// scale = 2^bits
+    pub fn with_bits(bits: u32) -> Result<Self> {
+        Self::new(BigInt::from(1) << bits as usize)
+    }
+
+    pub fn scale(&self) -> &BigInt {
+        &self.scale
+    }
+
+    /// round(x * scale) at level 1
+    pub fn encode(&self, x: f64) -> Result<PlainText<MOD>> {
+        if !x.is_finite() {
+            return Err(OctonionError::InvalidParameter(format!(
+                "{} can't be encoded",
+                x
+            )));
+        }
+        let scaled = x * self.scale.to_f64().unwrap();
+        let value = BigInt::from_f64(scaled.round()).ok_or_else(|| {
+            OctonionError::InvalidParameter(format!("{} * {} can't be encoded", x, self.scale))
+        })?;
+        signed::encode(&value)
+    }
+
+    /// pt as a value at scale^level, errors if it doesn't fit an f64
+    pub fn decode(&self, pt: &PlainText<MOD>, level: u32) -> Result<f64> {
+        let value = signed::decode(pt);
+        let d = self.scale.pow(level);
+        ratio_to_f64(&value, &d).ok_or_else(|| {
+            OctonionError::InvalidParameter(format!("{} / {} doesn't fit an f64", value, d))
+        })
+    }
+
+    pub fn encrypt(
+        &self,
+        schema: &Schema<MOD>,
+        x: f64,
+        pk: &PublicKey<MOD>,
+    ) -> Result<ScaledCipherText<MOD>> {
+        Ok(ScaledCipherText {
+            ct: schema.encrypt(self.encode(x)?, pk),
+            level: 1,
+        })
+    }
+
+    pub fn decrypt(
+        &self,
+        schema: &Schema<MOD>,
+        sct: ScaledCipherText<MOD>,
+        sk: &SecretKey<MOD>,
+    ) -> Result<f64> {
+        let level = sct.level;
+        self.decode(&schema.decrypt(sct.ct, sk)?, level)
+    }
+
+    /// move a decrypted value from scale^from to scale^to by rounded division.
+    /// there is no homomorphic division, so this only works after decryption
+    pub fn rescale(&self, pt: &PlainText<MOD>, from: u32, to: u32) -> Result<PlainText<MOD>> {
+        if to > from {
+            return Err(OctonionError::InvalidParameter(format!(
+                "can't rescale up from level {} to {}",
+                from, to
+            )));
+        }
+        let value = div_round(&signed::decode(pt), &self.scale.pow(from - to));
+        signed::encode(&value)
+    }
+
+    /// largest k such that a product of k values with |x| <= bound still fits the signed range
+    pub fn max_level(&self, bound: f64) -> u32 {
+        let max = signed::max_signed::<MOD>();
+        let factor = match BigInt::from_f64((bound * self.scale.to_f64().unwrap()).ceil()) {
+            Some(factor) if factor > BigInt::from(1) => factor,
+            _ => return u32::MAX,
+        };
+        let mut k = 0;
+        let mut product = factor.clone();
+        while product <= max {
+            k += 1;
+            product *= &factor;
+        }
+        k
+    }
+
+    /// first order bound on |decoded - exact| for a product of level values with |x| <= bound,
+    /// each rounded by at most 1 / (2 scale)
+    pub fn error_bound(&self, bound: f64, level: u32) -> f64 {
+        let e = 0.5 / self.scale.to_f64().unwrap();
+        level as f64 * bound.max(1.0).powi(level as i32 - 1) * e
+    }
+}
+
+impl<const MOD: &'static str> ScaledCipherText<MOD> {
+    /// self + rhs, errors if the levels differ, which is why there is no + operator
+    pub fn checked_add(&self, rhs: &Self) -> Result<Self> {
+        if self.level != rhs.level {
+            return Err(OctonionError::InvalidParameter(format!(
+                "can't add ciphertexts at levels {} and {}",
+                self.level, rhs.level
+            )));
+        }
+        Ok(ScaledCipherText {
+            ct: &self.ct + &rhs.ct,
+            level: self.level,
+        })
+    }
+}
+
+impl<const MOD: &'static str> Mul<&ScaledCipherText<MOD>> for &ScaledCipherText<MOD> {
+    type Output = ScaledCipherText<MOD>;
+    fn mul(self, rhs: &ScaledCipherText<MOD>) -> ScaledCipherText<MOD> {
+        ScaledCipherText {
+            ct: &self.ct * &rhs.ct,
+            level: self.level + rhs.level,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickcheck_macros::quickcheck;
+
+    use super::*;
+    use crate::consts::{M2203_STR, M31_STR, M521_STR};
+
+    const M: &str = M521_STR;
+
+    #[quickcheck]
+    fn test_encode_decode(x: i32, frac: u16) -> bool {
+        let fp = FixedPoint::<M>::with_bits(20).unwrap();
+        let x = x as f64 + frac as f64 / 65536.0;
+        let decoded = fp.decode(&fp.encode(x).unwrap(), 1).unwrap();
+        (decoded - x).abs() <= fp.error_bound(x.abs(), 1)
+    }
+
+    #[test]
+    fn test_homomorphic() {
+        let fp = FixedPoint::<M>::with_bits(16).unwrap();
+        let schema = Schema::<M>::new();
+        let (sk, pk) = schema.gen_sk_pk();
+        let a = fp.encrypt(&schema, 1.5, &pk).unwrap();
+        let b = fp.encrypt(&schema, -2.25, &pk).unwrap();
+        let c = fp.encrypt(&schema, 0.1, &pk).unwrap();
+
+        let prod = &a * &b;
+        assert_eq!(prod.level, 2);
+        assert_eq!(fp.decrypt(&schema, prod.clone(), &sk).unwrap(), -3.375);
+        let sum = prod.checked_add(&(&c * &c)).unwrap();
+        let decrypted = fp.decrypt(&schema, sum.clone(), &sk).unwrap();
+        assert!((decrypted - (-3.375 + 0.01)).abs() <= fp.error_bound(1.0, 2));
+
+        let pt = schema.decrypt(sum.ct, &sk).unwrap();
+        let rescaled = fp.rescale(&pt, 2, 1).unwrap();
+        assert!((fp.decode(&rescaled, 1).unwrap() - (-3.365)).abs() <= 2.0 / 65536.0);
+        assert!(fp.rescale(&pt, 1, 2).is_err());
+    }
+
+    #[test]
+    fn test_checked_add() {
+        let fp = FixedPoint::<M>::with_bits(16).unwrap();
+        let schema = Schema::<M>::new();
+        let (sk, pk) = schema.gen_sk_pk();
+        let a = fp.encrypt(&schema, 1.5, &pk).unwrap();
+        assert!(a.checked_add(&(&a * &a)).is_err());
+        let sum = a.checked_add(&a).unwrap();
+        assert_eq!(fp.decrypt(&schema, sum, &sk).unwrap(), 3.0);
+    }
+
+    #[test]
+    fn test_decode_not_finite() {
+        // 2^1100 at scale 1 is past f64::MAX
+        let fp = FixedPoint::<M2203_STR>::new(BigInt::from(1)).unwrap();
+        let pt = signed::encode::<M2203_STR>(&(BigInt::from(1) << 1100usize)).unwrap();
+        assert!(fp.decode(&pt, 1).is_err());
+        let pt = signed::encode::<M2203_STR>(&(BigInt::from(1) << 900usize)).unwrap();
+        assert!(fp.decode(&pt, 1).unwrap().is_finite());
+    }
+
+    #[test]
+    fn test_rescale_rounding() {
+        let fp = FixedPoint::<M>::new(BigInt::from(10)).unwrap();
+        for (value, expected) in [(15, 2), (14, 1), (-15, -1), (-16, -2), (-14, -1)].iter() {
+            let pt = signed::encode::<M>(&BigInt::from(*value)).unwrap();
+            let rescaled = fp.rescale(&pt, 2, 1).unwrap();
+            assert_eq!(signed::decode(&rescaled), BigInt::from(*expected));
+        }
+    }
+
+    #[test]
+    fn test_max_level() {
+        let m31 = FixedPoint::<M31_STR>::with_bits(20).unwrap();
+        assert_eq!(m31.max_level(512.0), 1);
+        assert_eq!(m31.max_level(1024.0), 0);
+        let m521 = FixedPoint::<M>::with_bits(20).unwrap();
+        assert_eq!(m521.max_level(512.0), 17);
+        let m2203 = FixedPoint::<M2203_STR>::with_bits(20).unwrap();
+        assert_eq!(m2203.max_level(512.0), 75);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(FixedPoint::<M31_STR>::with_bits(31).is_err());
+        assert!(FixedPoint::<M>::new(BigInt::from(0)).is_err());
+        let fp = FixedPoint::<M31_STR>::with_bits(20).unwrap();
+        assert!(fp.encode(f64::NAN).is_err());
+        assert!(matches!(
+            fp.encode(4096.0),
+            Err(OctonionError::OutOfRange { .. })
+        ));
+        assert!(fp.encode(-1023.5).is_ok());
+    }
+}
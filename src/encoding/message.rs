@@ -0,0 +1,171 @@
+//! byte strings as a sequence of plaintexts.
+//!
+//! the message is framed as an 8 byte big endian length followed by the bytes, zero padded
+//! to a multiple of chunk_size. each chunk is read as a big endian integer, which is below
+//! 2^(bits(p) - 1) < p, so every chunk fits one plaintext.
+use std::str::FromStr;
+
+use num_bigint::{BigInt, Sign};
+
+use crate::crypto::cipher_text::CipherText;
+use crate::crypto::{PlainText, PublicKey, Schema, SecretKey};
+use crate::error::{OctonionError, Result};
+
+/// bytes of the length prefix
+pub const LENGTH_PREFIX: usize = 8;
+
+/// bytes per plaintext, (bits(p) - 1) / 8
+pub fn chunk_size<const MOD: &'static str>() -> Result<usize> {
+    let m = BigInt::from_str(MOD).unwrap();
+    let size = (m.bits() as usize).saturating_sub(1) / 8;
+    if size == 0 {
+        return Err(OctonionError::InvalidModulus {
+            modulus: m,
+            reason: "must be larger than 2^8 to hold a byte".to_string(),
+        });
+    }
+    Ok(size)
+}
+
+pub fn encode<const MOD: &'static str>(message: &[u8]) -> Result<Vec<PlainText<MOD>>> {
+    let size = chunk_size::<MOD>()?;
+    let mut framed = Vec::with_capacity(LENGTH_PREFIX + message.len() + size);
+    framed.extend_from_slice(&(message.len() as u64).to_be_bytes());
+    framed.extend_from_slice(message);
+    let padded = framed.len().div_ceil(size) * size;
+    framed.resize(padded, 0);
+
+    Ok(framed
+        .chunks(size)
+        .map(|chunk| PlainText {
+            value: BigInt::from_bytes_be(Sign::Plus, chunk),
+        })
+        .collect())
+}
+
+pub fn decode<const MOD: &'static str>(pts: &[PlainText<MOD>]) -> Result<Vec<u8>> {
+    let size = chunk_size::<MOD>()?;
+    let mut framed = Vec::with_capacity(pts.len() * size);
+    for (i, pt) in pts.iter().enumerate() {
+        let (sign, bytes) = pt.value.to_bytes_be();
+        if sign == Sign::Minus || bytes.len() > size {
+            return Err(OctonionError::MalformedMessage(format!(
+                "chunk {} = {} doesn't fit {} bytes",
+                i, pt.value, size
+            )));
+        }
+        framed.resize(framed.len() + size - bytes.len(), 0);
+        framed.extend(bytes);
+    }
+
+    if framed.len() < LENGTH_PREFIX {
+        return Err(OctonionError::MalformedMessage(
+            "missing length prefix".to_string(),
+        ));
+    }
+    let mut prefix = [0; LENGTH_PREFIX];
+    prefix.copy_from_slice(&framed[..LENGTH_PREFIX]);
+    let len = u64::from_be_bytes(prefix);
+    let body = &framed[LENGTH_PREFIX..];
+    if len > body.len() as u64 {
+        return Err(OctonionError::MalformedMessage(format!(
+            "length {} but only {} bytes",
+            len,
+            body.len()
+        )));
+    }
+    let (message, padding) = body.split_at(len as usize);
+    if padding.len() >= size || padding.iter().any(|b| *b != 0) {
+        return Err(OctonionError::MalformedMessage(
+            "padding must be fewer than one chunk of zeros".to_string(),
+        ));
+    }
+    Ok(message.to_vec())
+}
+
+pub fn encrypt<const MOD: &'static str>(
+    schema: &Schema<MOD>,
+    message: &[u8],
+    pk: &PublicKey<MOD>,
+) -> Result<Vec<CipherText<MOD>>> {
+    Ok(encode(message)?
+        .into_iter()
+        .map(|pt| schema.encrypt(pt, pk))
+        .collect())
+}
+
+pub fn decrypt<const MOD: &'static str>(
+    schema: &Schema<MOD>,
+    cts: Vec<CipherText<MOD>>,
+    sk: &SecretKey<MOD>,
+) -> Result<Vec<u8>> {
+    let pts = cts
+        .into_iter()
+        .map(|ct| schema.decrypt(ct, sk))
+        .collect::<Result<Vec<_>>>()?;
+    decode(&pts)
+}
+
+#[cfg(test)]
+mod tests {
+    use quickcheck_macros::quickcheck;
+
+    use super::*;
+    use crate::consts::{M31_STR, M521_STR};
+
+    const M: &str = M521_STR;
+
+    #[quickcheck]
+    fn test_encode_decode(message: Vec<u8>) -> bool {
+        decode(&encode::<M>(&message).unwrap()).unwrap() == message
+            && decode(&encode::<M31_STR>(&message).unwrap()).unwrap() == message
+            && decode(&encode::<"257">(&message).unwrap()).unwrap() == message
+    }
+
+    #[test]
+    fn test_chunk_size() {
+        assert_eq!(chunk_size::<M>().unwrap(), 65);
+        assert_eq!(chunk_size::<M31_STR>().unwrap(), 3);
+        assert_eq!(chunk_size::<"257">().unwrap(), 1);
+        assert!(chunk_size::<"251">().is_err());
+        // prefix and body of 3 bytes fill 11 bytes, 4 chunks of 3
+        assert_eq!(encode::<M31_STR>(&[0, 0, 0]).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_encrypt_decrypt() {
+        let schema = Schema::<M>::new();
+        let (sk, pk) = schema.gen_sk_pk();
+        for message in [
+            &b""[..],
+            &b"octonion"[..],
+            &[0; 100][..],
+            &[255; 57][..],
+            &[1, 0, 0, 0][..],
+        ]
+        .iter()
+        {
+            let cts = encrypt(&schema, message, &pk).unwrap();
+            assert_eq!(&decrypt(&schema, cts, &sk).unwrap()[..], *message);
+        }
+    }
+
+    #[test]
+    fn test_decode_malformed() {
+        let mut pts = encode::<M31_STR>(b"hello").unwrap();
+        assert!(decode::<M31_STR>(&pts[..2]).is_err());
+        assert!(decode::<M31_STR>(&[]).is_err());
+
+        let mut extra = pts.clone();
+        extra.push(PlainText {
+            value: BigInt::from(0),
+        });
+        assert!(decode(&extra).is_err());
+
+        pts[1].value = BigInt::from(1) << 24;
+        assert!(matches!(
+            decode(&pts),
+            Err(OctonionError::MalformedMessage(_))
+        ));
+    }
+}
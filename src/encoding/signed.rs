@@ -0,0 +1,231 @@
+//! signed integers in the centered representation [-(p - 1) / 2, (p - 1) / 2].
+//!
+//! x < 0 is encoded as p + x. sums and products of encoded values decode to the
+//! integer result as long as that result is in range too. decode can't tell, a result out
+//! of range silently wraps around: a product of k factors with |x| <= b fits if
+//! b^k <= max_signed. BoundedCipherText tracks such a bound in the clear and refuses
+//! arithmetic that could leave the range.
+use std::str::FromStr;
+
+use num_bigint::BigInt;
+
+use crate::crypto::cipher_text::CipherText;
+use crate::crypto::{PlainText, PublicKey, Schema, SecretKey};
+use crate::error::{OctonionError, Result};
+
+/// ciphertext of a signed integer x with |x| <= bound
+#[derive(Debug, Clone)]
+pub struct BoundedCipherText<const MOD: &'static str> {
+    pub ct: CipherText<MOD>,
+    pub bound: BigInt,
+}
+
+/// (p - 1) / 2, the largest magnitude that decodes back
+pub fn max_signed<const MOD: &'static str>() -> BigInt {
+    let m = BigInt::from_str(MOD).unwrap();
+    (m - 1) / 2
+}
+
+/// is |x| <= max_signed
+pub fn fits<const MOD: &'static str>(x: &BigInt) -> bool {
+    let bound = max_signed::<MOD>();
+    -&bound <= *x && *x <= bound
+}
+
+pub fn encode<const MOD: &'static str>(x: &BigInt) -> Result<PlainText<MOD>> {
+    let m = BigInt::from_str(MOD).unwrap();
+    if !fits::<MOD>(x) {
+        return Err(OctonionError::OutOfRange {
+            value: x.clone(),
+            bound: max_signed::<MOD>(),
+        });
+    }
+    let mut value = x % &m;
+    if value < BigInt::from(0) {
+        value += &m;
+    }
+    Ok(PlainText { value })
+}
+
+/// bound if it fits, OutOfRange otherwise
+fn check_bound<const MOD: &'static str>(bound: BigInt) -> Result<BigInt> {
+    if !fits::<MOD>(&bound) {
+        return Err(OctonionError::OutOfRange {
+            value: bound,
+            bound: max_signed::<MOD>(),
+        });
+    }
+    Ok(bound)
+}
+
+/// value in (p / 2, p) is negative
+pub fn decode<const MOD: &'static str>(pt: &PlainText<MOD>) -> BigInt {
+    let m = BigInt::from_str(MOD).unwrap();
+    let mut value = &pt.value % &m;
+    if value < BigInt::from(0) {
+        value += &m;
+    }
+    if value > max_signed::<MOD>() {
+        value -= &m;
+    }
+    value
+}
+
+/// decode, but OutOfRange unless |x| <= bound
+pub fn decode_bounded<const MOD: &'static str>(
+    pt: &PlainText<MOD>,
+    bound: &BigInt,
+) -> Result<BigInt> {
+    let value = decode(pt);
+    if -bound > value || value > *bound {
+        return Err(OctonionError::OutOfRange {
+            value,
+            bound: bound.clone(),
+        });
+    }
+    Ok(value)
+}
+
+impl<const MOD: &'static str> BoundedCipherText<MOD> {
+    /// encrypt x with bound |x|
+    pub fn encrypt(schema: &Schema<MOD>, x: &BigInt, pk: &PublicKey<MOD>) -> Result<Self> {
+        Ok(Self {
+            ct: schema.encrypt(encode(x)?, pk),
+            bound: if x < &BigInt::from(0) { -x } else { x.clone() },
+        })
+    }
+
+    /// decrypt, OutOfRange if the result is beyond the bound, e.g. for a tampered ciphertext
+    pub fn decrypt(&self, schema: &Schema<MOD>, sk: &SecretKey<MOD>) -> Result<BigInt> {
+        decode_bounded(&schema.decrypt(self.ct.clone(), sk)?, &self.bound)
+    }
+
+    /// self + rhs, OutOfRange if the sum might not fit
+    pub fn checked_add(&self, rhs: &Self) -> Result<Self> {
+        Ok(Self {
+            bound: check_bound::<MOD>(&self.bound + &rhs.bound)?,
+            ct: &self.ct + &rhs.ct,
+        })
+    }
+
+    /// self - rhs, OutOfRange if the difference might not fit
+    pub fn checked_sub(&self, rhs: &Self) -> Result<Self> {
+        Ok(Self {
+            bound: check_bound::<MOD>(&self.bound + &rhs.bound)?,
+            ct: &self.ct - &rhs.ct,
+        })
+    }
+
+    /// self rhs, OutOfRange if the product might not fit
+    pub fn checked_mul(&self, rhs: &Self) -> Result<Self> {
+        Ok(Self {
+            bound: check_bound::<MOD>(&self.bound * &rhs.bound)?,
+            ct: &self.ct * &rhs.ct,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use quickcheck_macros::quickcheck;
+
+    use super::*;
+    use crate::consts::M521_STR;
+    use crate::crypto::Schema;
+
+    const M: &str = M521_STR;
+
+    #[quickcheck]
+    fn test_encode_decode(x: i64) -> bool {
+        let x = BigInt::from(x);
+        decode(&encode::<M>(&x).unwrap()) == x
+    }
+
+    #[test]
+    fn test_range() {
+        let bound = max_signed::<"7">();
+        assert_eq!(bound, BigInt::from(3));
+        for x in -3..=3 {
+            assert_eq!(
+                decode(&encode::<"7">(&BigInt::from(x)).unwrap()),
+                BigInt::from(x)
+            );
+        }
+        assert_eq!(
+            encode::<"7">(&BigInt::from(-1)).unwrap().value,
+            BigInt::from(6)
+        );
+        for x in [-4, 4, 100].iter() {
+            assert_eq!(
+                encode::<"7">(&BigInt::from(*x)).unwrap_err(),
+                OctonionError::OutOfRange {
+                    value: BigInt::from(*x),
+                    bound: bound.clone(),
+                }
+            );
+        }
+    }
+
+    #[test]
+    fn test_homomorphic() {
+        let schema = Schema::<M>::new();
+        let (sk, pk) = schema.gen_sk_pk();
+        let enc = |x: i64| schema.encrypt(encode(&BigInt::from(x)).unwrap(), &pk);
+
+        let prod = &enc(-3) * &enc(5);
+        assert_eq!(
+            decode(&schema.decrypt(prod, &sk).unwrap()),
+            BigInt::from(-15)
+        );
+        let sum = &enc(-30) + &enc(5);
+        assert_eq!(
+            decode(&schema.decrypt(sum, &sk).unwrap()),
+            BigInt::from(-25)
+        );
+    }
+
+    #[quickcheck]
+    fn test_homomorphic_mul(a: i64, b: i64) -> bool {
+        let schema = Schema::<M>::new();
+        let (sk, pk) = schema.gen_sk_pk();
+        let ca = schema.encrypt(encode(&BigInt::from(a)).unwrap(), &pk);
+        let cb = schema.encrypt(encode(&BigInt::from(b)).unwrap(), &pk);
+        let prod = decode(&schema.decrypt(&ca * &cb, &sk).unwrap());
+        let sum = decode(&schema.decrypt(&ca + &cb, &sk).unwrap());
+        prod == BigInt::from(a) * BigInt::from(b) && sum == BigInt::from(a) + BigInt::from(b)
+    }
+
+    #[test]
+    fn test_overflow() {
+        let schema = Schema::<M>::new();
+        let (sk, pk) = schema.gen_sk_pk();
+        let x = BigInt::from(1) << 300usize;
+        let a = BoundedCipherText::encrypt(&schema, &x, &pk).unwrap();
+        let b = BoundedCipherText::encrypt(&schema, &BigInt::from(-3), &pk).unwrap();
+
+        let prod = a.checked_mul(&b).unwrap();
+        assert_eq!(prod.bound, 3 * &x);
+        assert_eq!(prod.decrypt(&schema, &sk).unwrap(), -3 * &x);
+        let diff = a.checked_sub(&b).unwrap();
+        assert_eq!(diff.decrypt(&schema, &sk).unwrap(), &x + 3);
+
+        // x^2 = 2^600 is past (p - 1) / 2 = 2^520 - 1 and wraps around
+        let square = decode(&schema.decrypt(&a.ct * &a.ct, &sk).unwrap());
+        assert_ne!(square, &x * &x);
+        assert!(matches!(
+            a.checked_mul(&a),
+            Err(OctonionError::OutOfRange { .. })
+        ));
+        let big = BoundedCipherText::encrypt(&schema, &max_signed::<M>(), &pk).unwrap();
+        assert!(big.checked_add(&b).is_err());
+        assert!(big.checked_sub(&b).is_err());
+
+        assert_eq!(
+            decode_bounded(&encode::<M>(&BigInt::from(-5)).unwrap(), &BigInt::from(4)),
+            Err(OctonionError::OutOfRange {
+                value: BigInt::from(-5),
+                bound: BigInt::from(4),
+            })
+        );
+    }
+}
@@ -0,0 +1,95 @@
+use std::fmt::{Display, Formatter};
+
+use num_bigint::BigInt;
+
+use crate::crypto::GHSearchStats;
+
+/// errors returned by the public api
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OctonionError {
+    /// value has no inverse mod modulus
+    NotInvertible {
+        value: BigInt,
+        modulus: BigInt,
+    },
+    /// value has no square root mod modulus
+    NotResidue {
+        value: BigInt,
+        modulus: BigInt,
+    },
+    /// modulus can't be used, e.g. even or too small
+    InvalidModulus {
+        modulus: BigInt,
+        reason: String,
+    },
+    /// value is not in [0, modulus), typically it was made under another modulus
+    ModulusMismatch {
+        value: BigInt,
+        modulus: BigInt,
+    },
+    /// value can't be encoded, its magnitude must be at most bound
+    OutOfRange {
+        value: BigInt,
+        bound: BigInt,
+    },
+    /// shapes of matrices or vectors don't fit
+    DimensionMismatch(String),
+    /// argument out of its domain, e.g. asking for a 1 bit prime
+    InvalidParameter(String),
+    /// randomized search gave up
+    SearchExhausted {
+        attempts: usize,
+        reason: String,
+        /// rejections by reason, for the g, h search
+        stats: Option<GHSearchStats>,
+    },
+    /// g and h of a schema break an invariant the encoding depends on
+    InvalidSchema(String),
+    MalformedKey(String),
+    MalformedCiphertext(String),
+    /// decrypted chunks don't form a message
+    MalformedMessage(String),
+}
+
+pub type Result<T> = std::result::Result<T, OctonionError>;
+
+impl Display for OctonionError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        match self {
+            OctonionError::NotInvertible { value, modulus } => {
+                write!(f, "{} has no inverse mod {}", value, modulus)
+            }
+            OctonionError::NotResidue { value, modulus } => {
+                write!(f, "{} is not a residue mod {}", value, modulus)
+            }
+            OctonionError::InvalidModulus { modulus, reason } => {
+                write!(f, "invalid modulus {}: {}", modulus, reason)
+            }
+            OctonionError::ModulusMismatch { value, modulus } => {
+                write!(f, "{} is out of range mod {}", value, modulus)
+            }
+            OctonionError::OutOfRange { value, bound } => {
+                write!(
+                    f,
+                    "{} is out of the encodable range, |x| <= {}",
+                    value, bound
+                )
+            }
+            OctonionError::DimensionMismatch(reason) => write!(f, "dimension mismatch: {}", reason),
+            OctonionError::InvalidParameter(reason) => write!(f, "invalid parameter: {}", reason),
+            OctonionError::SearchExhausted {
+                attempts, reason, ..
+            } => {
+                write!(f, "gave up after {} attempts: {}", attempts, reason)
+            }
+            OctonionError::InvalidSchema(reason) => write!(f, "invalid schema: {}", reason),
+            OctonionError::MalformedKey(reason) => write!(f, "malformed key: {}", reason),
+            OctonionError::MalformedCiphertext(reason) => {
+                write!(f, "malformed ciphertext: {}", reason)
+            }
+            OctonionError::MalformedMessage(reason) => write!(f, "malformed message: {}", reason),
+        }
+    }
+}
+
+impl std::error::Error for OctonionError {}
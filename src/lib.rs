@@ -1,5 +1,11 @@
 #![feature(const_generics)]
+pub mod analysis;
+pub mod circuit;
 pub mod consts;
 pub mod crypto;
+pub mod encoding;
+pub mod error;
+pub mod linalg;
+pub mod polynomial;
 pub mod types;
 pub mod utils;
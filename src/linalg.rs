@@ -0,0 +1,570 @@
+use std::fmt::Formatter;
+use std::ops::{Add, Index, IndexMut, Mul, Neg, Sub};
+use std::{fmt::Display, str::FromStr};
+
+use num_bigint::BigInt;
+
+use crate::error::{OctonionError, Result};
+use crate::types::Octonion;
+use crate::utils::inverse;
+
+#[cfg(test)]
+mod tests;
+
+/// matrix over F_MOD, row major.
+/// MOD must be prime for rref, rank, determinant, inverse, kernel and solve,
+/// otherwise they return NotInvertible for the first pivot without an inverse.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Matrix<const MOD: &'static str> {
+    rows: usize,
+    cols: usize,
+    data: Vec<BigInt>,
+}
+
+/// column vector over F_MOD
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Vector<const MOD: &'static str> {
+    data: Vec<BigInt>,
+}
+
+/// x mod m in [0, m)
+fn reduce(x: BigInt, m: &BigInt) -> BigInt {
+    let mut x = x % m;
+    if x < BigInt::from(0) {
+        x += m;
+    }
+    x
+}
+
+impl<const MOD: &'static str> Matrix<MOD> {
+    /// build a rows x cols matrix from row major data, reducing every entry mod MOD
+    pub fn new(rows: usize, cols: usize, data: Vec<BigInt>) -> Result<Self> {
+        if data.len() != rows * cols {
+            return Err(OctonionError::DimensionMismatch(format!(
+                "{} x {} matrix needs {} entries, got {}",
+                rows,
+                cols,
+                rows * cols,
+                data.len()
+            )));
+        }
+        let m = BigInt::from_str(MOD).unwrap();
+        let data = data.into_iter().map(|x| reduce(x, &m)).collect();
+        Ok(Self { rows, cols, data })
+    }
+
+    /// build a matrix from its rows, all rows must have the same length
+    pub fn from_rows(rows: Vec<Vec<BigInt>>) -> Result<Self> {
+        let n_rows = rows.len();
+        let n_cols = rows.first().map_or(0, |row| row.len());
+        let mut data = Vec::with_capacity(n_rows * n_cols);
+        for (i, row) in rows.into_iter().enumerate() {
+            if row.len() != n_cols {
+                return Err(OctonionError::DimensionMismatch(format!(
+                    "row {} has {} entries, expected {}",
+                    i,
+                    row.len(),
+                    n_cols
+                )));
+            }
+            data.extend(row);
+        }
+        Self::new(n_rows, n_cols, data)
+    }
+
+    pub fn zeros(rows: usize, cols: usize) -> Self {
+        Self {
+            rows,
+            cols,
+            data: vec![BigInt::from(0); rows * cols],
+        }
+    }
+
+    /// n x n identity matrix
+    pub fn identity(n: usize) -> Self {
+        let mut a = Self::zeros(n, n);
+        for i in 0..n {
+            a[(i, i)] = BigInt::from(1);
+        }
+        a
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    pub fn row(&self, i: usize) -> Vector<MOD> {
+        Vector {
+            data: self.data[i * self.cols..(i + 1) * self.cols].to_vec(),
+        }
+    }
+
+    pub fn col(&self, j: usize) -> Vector<MOD> {
+        Vector {
+            data: (0..self.rows).map(|i| self[(i, j)].clone()).collect(),
+        }
+    }
+
+    /// row major entries
+    pub fn into_vec(self) -> Vec<BigInt> {
+        self.data
+    }
+
+    pub fn transpose(&self) -> Self {
+        let mut t = Self::zeros(self.cols, self.rows);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                t[(j, i)] = self[(i, j)].clone();
+            }
+        }
+        t
+    }
+
+    /// k * self
+    pub fn scale(&self, k: &BigInt) -> Self {
+        let m = BigInt::from_str(MOD).unwrap();
+        Self {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(|x| reduce(x * k, &m)).collect(),
+        }
+    }
+
+    /// [self | rhs]
+    pub fn augment(&self, rhs: &Self) -> Result<Self> {
+        if self.rows != rhs.rows {
+            return Err(OctonionError::DimensionMismatch(format!(
+                "can't augment {} rows with {} rows",
+                self.rows, rhs.rows
+            )));
+        }
+        let mut a = Self::zeros(self.rows, self.cols + rhs.cols);
+        for i in 0..self.rows {
+            for j in 0..self.cols {
+                a[(i, j)] = self[(i, j)].clone();
+            }
+            for j in 0..rhs.cols {
+                a[(i, self.cols + j)] = rhs[(i, j)].clone();
+            }
+        }
+        Ok(a)
+    }
+
+    fn swap_rows(&mut self, i: usize, j: usize) {
+        if i == j {
+            return;
+        }
+        for k in 0..self.cols {
+            self.data.swap(i * self.cols + k, j * self.cols + k);
+        }
+    }
+
+    /// gaussian elimination.
+    /// returns the reduced row echelon form and the pivot column of each nonzero row.
+    pub fn rref(&self) -> Result<(Self, Vec<usize>)> {
+        let m = BigInt::from_str(MOD).unwrap();
+        let zero = BigInt::from(0);
+        let mut a = self.clone();
+        let mut pivots = Vec::new();
+
+        let mut row = 0;
+        for col in 0..a.cols {
+            if row == a.rows {
+                break;
+            }
+            let pivot = match (row..a.rows).find(|&i| a[(i, col)] != zero) {
+                Some(pivot) => pivot,
+                None => continue,
+            };
+            a.swap_rows(row, pivot);
+
+            let inv = inverse(a[(row, col)].clone(), m.clone())?;
+            for j in col..a.cols {
+                a[(row, j)] = (&a[(row, j)] * &inv) % &m;
+            }
+            for i in 0..a.rows {
+                if i == row || a[(i, col)] == zero {
+                    continue;
+                }
+                let factor = a[(i, col)].clone();
+                for j in col..a.cols {
+                    let x = &a[(i, j)] - &factor * &a[(row, j)];
+                    a[(i, j)] = reduce(x, &m);
+                }
+            }
+
+            pivots.push(col);
+            row += 1;
+        }
+        Ok((a, pivots))
+    }
+
+    pub fn rank(&self) -> Result<usize> {
+        Ok(self.rref()?.1.len())
+    }
+
+    /// det(self), None if self is not square
+    pub fn determinant(&self) -> Result<Option<BigInt>> {
+        if !self.is_square() {
+            return Ok(None);
+        }
+        let m = BigInt::from_str(MOD).unwrap();
+        let zero = BigInt::from(0);
+        let n = self.rows;
+        let mut a = self.clone();
+        let mut det = BigInt::from(1);
+
+        for col in 0..n {
+            let pivot = match (col..n).find(|&i| a[(i, col)] != zero) {
+                Some(pivot) => pivot,
+                None => return Ok(Some(zero)),
+            };
+            if pivot != col {
+                a.swap_rows(col, pivot);
+                det = -det;
+            }
+            det = reduce(det * &a[(col, col)], &m);
+
+            let inv = inverse(a[(col, col)].clone(), m.clone())?;
+            for i in col + 1..n {
+                if a[(i, col)] == zero {
+                    continue;
+                }
+                let factor = (&a[(i, col)] * &inv) % &m;
+                for j in col..n {
+                    let x = &a[(i, j)] - &factor * &a[(col, j)];
+                    a[(i, j)] = reduce(x, &m);
+                }
+            }
+        }
+        Ok(Some(det))
+    }
+
+    /// self^-1, None if self is not square or singular
+    pub fn inverse(&self) -> Result<Option<Self>> {
+        if !self.is_square() {
+            return Ok(None);
+        }
+        let n = self.rows;
+        if n == 0 {
+            return Ok(Some(Self::zeros(0, 0)));
+        }
+        let (r, pivots) = self.augment(&Self::identity(n)).unwrap().rref()?;
+        if pivots.len() < n || pivots[n - 1] != n - 1 {
+            return Ok(None);
+        }
+        let mut inv = Self::zeros(n, n);
+        for i in 0..n {
+            for j in 0..n {
+                inv[(i, j)] = r[(i, n + j)].clone();
+            }
+        }
+        Ok(Some(inv))
+    }
+
+    /// basis of { x | self x = 0 }
+    pub fn kernel(&self) -> Result<Vec<Vector<MOD>>> {
+        let m = BigInt::from_str(MOD).unwrap();
+        let (r, pivots) = self.rref()?;
+        let mut basis = Vec::new();
+        for free in (0..self.cols).filter(|j| !pivots.contains(j)) {
+            let mut x = Vector::zeros(self.cols);
+            x[free] = BigInt::from(1);
+            for (i, &p) in pivots.iter().enumerate() {
+                x[p] = reduce(-&r[(i, free)], &m);
+            }
+            basis.push(x);
+        }
+        Ok(basis)
+    }
+
+    /// find x, self x = b, None if there is none.
+    /// if there are many solutions, free variables are set to 0.
+    pub fn solve(&self, b: &Vector<MOD>) -> Result<Option<Vector<MOD>>> {
+        if b.len() != self.rows {
+            return Err(OctonionError::DimensionMismatch(format!(
+                "can't solve {} x {} system for vector of length {}",
+                self.rows,
+                self.cols,
+                b.len()
+            )));
+        }
+        let b = Self {
+            rows: b.len(),
+            cols: 1,
+            data: b.data.clone(),
+        };
+        let (r, pivots) = self.augment(&b)?.rref()?;
+        if pivots.last() == Some(&self.cols) {
+            // 0 = 1
+            return Ok(None);
+        }
+        let mut x = Vector::zeros(self.cols);
+        for (i, &p) in pivots.iter().enumerate() {
+            x[p] = r[(i, self.cols)].clone();
+        }
+        Ok(Some(x))
+    }
+}
+
+impl<const MOD: &'static str> Vector<MOD> {
+    /// reduce every entry mod MOD
+    pub fn new(data: Vec<BigInt>) -> Self {
+        let m = BigInt::from_str(MOD).unwrap();
+        Self {
+            data: data.into_iter().map(|x| reduce(x, &m)).collect(),
+        }
+    }
+
+    pub fn zeros(n: usize) -> Self {
+        Self {
+            data: vec![BigInt::from(0); n],
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        let zero = BigInt::from(0);
+        self.data.iter().all(|x| *x == zero)
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, BigInt> {
+        self.data.iter()
+    }
+
+    pub fn into_vec(self) -> Vec<BigInt> {
+        self.data
+    }
+
+    /// <self, rhs>
+    pub fn dot(&self, rhs: &Self) -> BigInt {
+        assert_eq!(self.len(), rhs.len(), "dot of different lengths");
+        let m = BigInt::from_str(MOD).unwrap();
+        let mut ans = BigInt::from(0);
+        for (x, y) in self.data.iter().zip(rhs.data.iter()) {
+            ans += x * y;
+        }
+        ans % m
+    }
+
+    /// k * self
+    pub fn scale(&self, k: &BigInt) -> Self {
+        let m = BigInt::from_str(MOD).unwrap();
+        Self {
+            data: self.data.iter().map(|x| reduce(x * k, &m)).collect(),
+        }
+    }
+
+    /// self as an octonion, None if self.len() != 8
+    pub fn to_octonion(&self) -> Option<Octonion<MOD>> {
+        if self.len() != 8 {
+            return None;
+        }
+        let mut o = Octonion::zero();
+        for i in 0..8 {
+            o[i] = self[i].clone();
+        }
+        Some(o)
+    }
+}
+
+impl<const MOD: &'static str> From<&Octonion<MOD>> for Vector<MOD> {
+    fn from(o: &Octonion<MOD>) -> Self {
+        Vector {
+            data: (0..8).map(|i| o[i].clone()).collect(),
+        }
+    }
+}
+
+impl<const MOD: &'static str> Index<(usize, usize)> for Matrix<MOD> {
+    type Output = BigInt;
+    fn index(&self, (i, j): (usize, usize)) -> &BigInt {
+        if i >= self.rows || j >= self.cols {
+            panic!(
+                "index ({}, {}) out of bounds for {} x {} matrix.",
+                i, j, self.rows, self.cols
+            );
+        }
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl<const MOD: &'static str> IndexMut<(usize, usize)> for Matrix<MOD> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut BigInt {
+        if i >= self.rows || j >= self.cols {
+            panic!(
+                "index ({}, {}) out of bounds for {} x {} matrix.",
+                i, j, self.rows, self.cols
+            );
+        }
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+impl<const MOD: &'static str> Index<usize> for Vector<MOD> {
+    type Output = BigInt;
+    fn index(&self, i: usize) -> &BigInt {
+        &self.data[i]
+    }
+}
+
+impl<const MOD: &'static str> IndexMut<usize> for Vector<MOD> {
+    fn index_mut(&mut self, i: usize) -> &mut BigInt {
+        &mut self.data[i]
+    }
+}
+
+impl<const MOD: &'static str> Add<&Matrix<MOD>> for &Matrix<MOD> {
+    type Output = Matrix<MOD>;
+    fn add(self, rhs: &Matrix<MOD>) -> Matrix<MOD> {
+        assert!(
+            self.rows == rhs.rows && self.cols == rhs.cols,
+            "can't add {} x {} and {} x {} matrices",
+            self.rows,
+            self.cols,
+            rhs.rows,
+            rhs.cols
+        );
+        let m = BigInt::from_str(MOD).unwrap();
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self
+                .data
+                .iter()
+                .zip(rhs.data.iter())
+                .map(|(x, y)| (x + y) % &m)
+                .collect(),
+        }
+    }
+}
+
+impl<const MOD: &'static str> Sub<&Matrix<MOD>> for &Matrix<MOD> {
+    type Output = Matrix<MOD>;
+    fn sub(self, rhs: &Matrix<MOD>) -> Matrix<MOD> {
+        self + &(-rhs)
+    }
+}
+
+impl<const MOD: &'static str> Neg for &Matrix<MOD> {
+    type Output = Matrix<MOD>;
+    fn neg(self) -> Matrix<MOD> {
+        let m = BigInt::from_str(MOD).unwrap();
+        Matrix {
+            rows: self.rows,
+            cols: self.cols,
+            data: self.data.iter().map(|x| reduce(-x, &m)).collect(),
+        }
+    }
+}
+
+impl<const MOD: &'static str> Mul<&Matrix<MOD>> for &Matrix<MOD> {
+    type Output = Matrix<MOD>;
+    fn mul(self, rhs: &Matrix<MOD>) -> Matrix<MOD> {
+        assert_eq!(
+            self.cols, rhs.rows,
+            "can't multiply {} x {} and {} x {} matrices",
+            self.rows, self.cols, rhs.rows, rhs.cols
+        );
+        let m = BigInt::from_str(MOD).unwrap();
+        let mut ans = Matrix::zeros(self.rows, rhs.cols);
+        for i in 0..self.rows {
+            for j in 0..rhs.cols {
+                let mut x = BigInt::from(0);
+                for k in 0..self.cols {
+                    x += &self[(i, k)] * &rhs[(k, j)];
+                }
+                ans[(i, j)] = x % &m;
+            }
+        }
+        ans
+    }
+}
+
+impl<const MOD: &'static str> Mul<&Vector<MOD>> for &Matrix<MOD> {
+    type Output = Vector<MOD>;
+    fn mul(self, rhs: &Vector<MOD>) -> Vector<MOD> {
+        assert_eq!(
+            self.cols,
+            rhs.len(),
+            "can't multiply {} x {} matrix and vector of length {}",
+            self.rows,
+            self.cols,
+            rhs.len()
+        );
+        Vector {
+            data: (0..self.rows).map(|i| self.row(i).dot(rhs)).collect(),
+        }
+    }
+}
+
+impl<const MOD: &'static str> Add<&Vector<MOD>> for &Vector<MOD> {
+    type Output = Vector<MOD>;
+    fn add(self, rhs: &Vector<MOD>) -> Vector<MOD> {
+        assert_eq!(self.len(), rhs.len(), "add of different lengths");
+        let m = BigInt::from_str(MOD).unwrap();
+        Vector {
+            data: self
+                .data
+                .iter()
+                .zip(rhs.data.iter())
+                .map(|(x, y)| (x + y) % &m)
+                .collect(),
+        }
+    }
+}
+
+impl<const MOD: &'static str> Sub<&Vector<MOD>> for &Vector<MOD> {
+    type Output = Vector<MOD>;
+    fn sub(self, rhs: &Vector<MOD>) -> Vector<MOD> {
+        assert_eq!(self.len(), rhs.len(), "sub of different lengths");
+        let m = BigInt::from_str(MOD).unwrap();
+        Vector {
+            data: self
+                .data
+                .iter()
+                .zip(rhs.data.iter())
+                .map(|(x, y)| reduce(x - y, &m))
+                .collect(),
+        }
+    }
+}
+
+impl<const MOD: &'static str> Display for Matrix<MOD> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        for i in 0..self.rows {
+            write!(f, "[")?;
+            for j in 0..self.cols {
+                write!(f, " {}", self[(i, j)])?;
+            }
+            writeln!(f, " ]")?;
+        }
+        Ok(())
+    }
+}
+
+impl<const MOD: &'static str> Display for Vector<MOD> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "(")?;
+        for x in self.data.iter() {
+            write!(f, " {}", x)?;
+        }
+        write!(f, " )")?;
+        Ok(())
+    }
+}
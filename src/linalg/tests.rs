@@ -0,0 +1,148 @@
+use num_bigint::RandBigInt;
+use quickcheck_macros::quickcheck;
+
+use super::*;
+use crate::consts::M521_STR;
+
+const M: &str = M521_STR;
+
+fn random_matrix(rows: usize, cols: usize) -> Matrix<M> {
+    let m = BigInt::from_str(M).unwrap();
+    let mut rng = rand::thread_rng();
+    let data = (0..rows * cols)
+        .map(|_| rng.gen_bigint_range(&BigInt::from(0), &m))
+        .collect();
+    Matrix::new(rows, cols, data).unwrap()
+}
+
+fn random_vector(n: usize) -> Vector<M> {
+    let m = BigInt::from_str(M).unwrap();
+    let mut rng = rand::thread_rng();
+    Vector::new(
+        (0..n)
+            .map(|_| rng.gen_bigint_range(&BigInt::from(0), &m))
+            .collect(),
+    )
+}
+
+/// rows x cols matrix of rank r
+fn random_matrix_with_rank(rows: usize, cols: usize, r: usize) -> Matrix<M> {
+    &random_matrix(rows, r) * &random_matrix(r, cols)
+}
+
+#[quickcheck]
+fn test_inverse(n: u8) -> bool {
+    let n = (n % 8 + 1) as usize;
+    let a = random_matrix(n, n);
+    match a.inverse().unwrap() {
+        Some(a_inv) => &a * &a_inv == Matrix::identity(n) && &a_inv * &a == Matrix::identity(n),
+        None => a.determinant().unwrap() == Some(BigInt::from(0)),
+    }
+}
+
+#[quickcheck]
+fn test_determinant_mul(n: u8) -> bool {
+    let m = BigInt::from_str(M).unwrap();
+
+    let n = (n % 8 + 1) as usize;
+    let a = random_matrix(n, n);
+    let b = random_matrix(n, n);
+    let det = |a: &Matrix<M>| a.determinant().unwrap().unwrap();
+    det(&(&a * &b)) == (det(&a) * det(&b)) % m && det(&a) == det(&a.transpose())
+}
+
+#[test]
+fn test_determinant_small() {
+    // det [[1, 2], [3, 4]] = -2
+    let a = Matrix::<"7">::from_rows(vec![
+        vec![BigInt::from(1), BigInt::from(2)],
+        vec![BigInt::from(3), BigInt::from(4)],
+    ])
+    .unwrap();
+    assert_eq!(a.determinant().unwrap(), Some(BigInt::from(5)));
+    assert_eq!(a.rank().unwrap(), 2);
+
+    // second row is 2 * first row
+    let b = Matrix::<"7">::from_rows(vec![
+        vec![BigInt::from(1), BigInt::from(3)],
+        vec![BigInt::from(2), BigInt::from(6)],
+    ])
+    .unwrap();
+    assert_eq!(b.determinant().unwrap(), Some(BigInt::from(0)));
+    assert_eq!(b.rank().unwrap(), 1);
+    assert_eq!(b.inverse().unwrap(), None);
+    assert_eq!(Matrix::<"7">::zeros(2, 3).determinant().unwrap(), None);
+}
+
+#[test]
+fn test_empty_inverse() {
+    let a = Matrix::<"7">::zeros(0, 0);
+    assert_eq!(a.inverse().unwrap(), Some(a.clone()));
+}
+
+#[test]
+fn test_composite_modulus() {
+    // 2 has no inverse mod 4
+    let a = Matrix::<"4">::from_rows(vec![
+        vec![BigInt::from(2), BigInt::from(1)],
+        vec![BigInt::from(1), BigInt::from(1)],
+    ])
+    .unwrap();
+    let not_invertible = |e: OctonionError| matches!(e, OctonionError::NotInvertible { .. });
+    assert!(not_invertible(a.rref().unwrap_err()));
+    assert!(not_invertible(a.determinant().unwrap_err()));
+    assert!(not_invertible(a.inverse().unwrap_err()));
+    assert!(not_invertible(a.kernel().unwrap_err()));
+    assert!(not_invertible(a.solve(&Vector::zeros(2)).unwrap_err()));
+}
+
+#[quickcheck]
+fn test_rank_kernel(rows: u8, cols: u8, r: u8) -> bool {
+    let rows = (rows % 8 + 1) as usize;
+    let cols = (cols % 8 + 1) as usize;
+    let r = (r as usize) % (rows.min(cols) + 1);
+    let a = random_matrix_with_rank(rows, cols, r);
+    let kernel = a.kernel().unwrap();
+    let rank = a.rank().unwrap();
+    rank <= r
+        && rank + kernel.len() == cols
+        && kernel.iter().all(|x| (&a * x).is_zero() && !x.is_zero())
+}
+
+#[quickcheck]
+fn test_solve(rows: u8, cols: u8) -> bool {
+    let rows = (rows % 8 + 1) as usize;
+    let cols = (cols % 8 + 1) as usize;
+    let a = random_matrix(rows, cols);
+    let x0 = random_vector(cols);
+    let b = &a * &x0;
+    match a.solve(&b).unwrap() {
+        Some(x) => &a * &x == b,
+        None => false,
+    }
+}
+
+#[test]
+fn test_solve_inconsistent() {
+    // x + y = 1, 2x + 2y = 3
+    let a = Matrix::<"7">::from_rows(vec![
+        vec![BigInt::from(1), BigInt::from(1)],
+        vec![BigInt::from(2), BigInt::from(2)],
+    ])
+    .unwrap();
+    let b = Vector::new(vec![BigInt::from(1), BigInt::from(3)]);
+    assert_eq!(a.solve(&b).unwrap(), None);
+    assert!(matches!(
+        a.solve(&Vector::new(vec![BigInt::from(1)])),
+        Err(OctonionError::DimensionMismatch(_))
+    ));
+}
+
+#[quickcheck]
+fn test_transpose(rows: u8, cols: u8) -> bool {
+    let rows = (rows % 8 + 1) as usize;
+    let cols = (cols % 8 + 1) as usize;
+    let a = random_matrix(rows, cols);
+    let b = random_matrix(cols, rows);
+    a.transpose().transpose() == a && (&a * &b).transpose() == &b.transpose() * &a.transpose()
+}
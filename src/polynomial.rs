@@ -0,0 +1,618 @@
+//! multivariate polynomials over F_MOD and a small system solver.
+//!
+//! terms are kept sparse in lex order with x0 > x1 > ... . groebner_basis runs buchberger's
+//! algorithm and returns the reduced basis, so a zero dimensional system ends in a polynomial
+//! of the last variable alone. solve finds its roots, substitutes them and recurses.
+use std::collections::BTreeMap;
+use std::fmt::Formatter;
+use std::ops::{Add, Mul, Neg, Sub};
+use std::{fmt::Display, str::FromStr};
+
+use num_bigint::BigInt;
+
+use crate::error::{OctonionError, Result};
+use crate::utils::inverse;
+
+#[cfg(test)]
+mod tests;
+
+/// exponents of x0, ..., x_{nvars - 1}
+pub type Monomial = Vec<u32>;
+
+/// polynomial in nvars variables over F_MOD, MOD must be prime
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Polynomial<const MOD: &'static str> {
+    nvars: usize,
+    /// nonzero coefficients, the last key is the leading monomial
+    terms: BTreeMap<Monomial, BigInt>,
+}
+
+/// x mod m in [0, m)
+fn reduce(x: BigInt, m: &BigInt) -> BigInt {
+    let mut x = x % m;
+    if x < BigInt::from(0) {
+        x += m;
+    }
+    x
+}
+
+fn divides(a: &[u32], b: &[u32]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x <= y)
+}
+
+fn lcm(a: &[u32], b: &[u32]) -> Monomial {
+    a.iter().zip(b).map(|(x, y)| *x.max(y)).collect()
+}
+
+impl<const MOD: &'static str> Polynomial<MOD> {
+    pub fn zero(nvars: usize) -> Self {
+        Self {
+            nvars,
+            terms: BTreeMap::new(),
+        }
+    }
+
+    pub fn constant(nvars: usize, c: BigInt) -> Self {
+        Self::from_terms(nvars, vec![(vec![0; nvars], c)]).unwrap()
+    }
+
+    /// x_i
+    pub fn var(nvars: usize, i: usize) -> Self {
+        assert!(i < nvars, "x{} in {} variables", i, nvars);
+        let mut mono = vec![0; nvars];
+        mono[i] = 1;
+        Self::from_terms(nvars, vec![(mono, BigInt::from(1))]).unwrap()
+    }
+
+    /// sum of c x^mono, like terms are added and coefficients are reduced mod MOD
+    pub fn from_terms(nvars: usize, terms: Vec<(Monomial, BigInt)>) -> Result<Self> {
+        let m = BigInt::from_str(MOD).unwrap();
+        let mut p = Self::zero(nvars);
+        for (mono, c) in terms {
+            if mono.len() != nvars {
+                return Err(OctonionError::DimensionMismatch(format!(
+                    "monomial of {} variables in a polynomial of {}",
+                    mono.len(),
+                    nvars
+                )));
+            }
+            p.add_term(mono, c, &m);
+        }
+        Ok(p)
+    }
+
+    fn add_term(&mut self, mono: Monomial, c: BigInt, m: &BigInt) {
+        let c = match self.terms.remove(&mono) {
+            Some(old) => reduce(old + c, m),
+            None => reduce(c, m),
+        };
+        if c != BigInt::from(0) {
+            self.terms.insert(mono, c);
+        }
+    }
+
+    pub fn nvars(&self) -> usize {
+        self.nvars
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// zero counts as constant
+    pub fn is_constant(&self) -> bool {
+        self.terms.keys().all(|mono| mono.iter().all(|e| *e == 0))
+    }
+
+    /// (monomial, coefficient) in increasing lex order
+    pub fn terms(&self) -> impl Iterator<Item = (&Monomial, &BigInt)> {
+        self.terms.iter()
+    }
+
+    pub fn leading_term(&self) -> Option<(&Monomial, &BigInt)> {
+        self.terms.iter().next_back()
+    }
+
+    /// total degree, 0 for the zero polynomial
+    pub fn degree(&self) -> u32 {
+        self.terms
+            .keys()
+            .map(|mono| mono.iter().sum())
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// indices of the variables that appear
+    pub fn variables(&self) -> Vec<usize> {
+        (0..self.nvars)
+            .filter(|&i| self.terms.keys().any(|mono| mono[i] != 0))
+            .collect()
+    }
+
+    pub fn eval(&self, point: &[BigInt]) -> BigInt {
+        assert_eq!(point.len(), self.nvars, "point has wrong length");
+        let m = BigInt::from_str(MOD).unwrap();
+        let mut ans = BigInt::from(0);
+        for (mono, c) in self.terms.iter() {
+            let mut t = c.clone();
+            for (x, e) in point.iter().zip(mono) {
+                t = t * x.modpow(&BigInt::from(*e), &m) % &m;
+            }
+            ans += t;
+        }
+        reduce(ans, &m)
+    }
+
+    /// x_var = value, nvars is kept
+    pub fn substitute(&self, var: usize, value: &BigInt) -> Self {
+        let m = BigInt::from_str(MOD).unwrap();
+        let mut p = Self::zero(self.nvars);
+        for (mono, c) in self.terms.iter() {
+            let mut mono = mono.clone();
+            let e = std::mem::replace(&mut mono[var], 0);
+            p.add_term(mono, c * value.modpow(&BigInt::from(e), &m), &m);
+        }
+        p
+    }
+
+    pub fn scale(&self, k: &BigInt) -> Self {
+        let m = BigInt::from_str(MOD).unwrap();
+        let mut p = Self::zero(self.nvars);
+        for (mono, c) in self.terms.iter() {
+            p.add_term(mono.clone(), c * k, &m);
+        }
+        p
+    }
+
+    /// self with leading coefficient 1, zero stays zero
+    pub fn monic(&self) -> Result<Self> {
+        let m = BigInt::from_str(MOD).unwrap();
+        match self.leading_term() {
+            Some((_, c)) => Ok(self.scale(&inverse(c.clone(), m)?)),
+            None => Ok(self.clone()),
+        }
+    }
+
+    pub fn pow(&self, e: u32) -> Self {
+        let mut ans = Self::constant(self.nvars, BigInt::from(1));
+        for _ in 0..e {
+            ans = &ans * self;
+        }
+        ans
+    }
+
+    /// c x^mono self
+    fn mul_term(&self, mono: &[u32], c: &BigInt) -> Self {
+        let m = BigInt::from_str(MOD).unwrap();
+        let mut p = Self::zero(self.nvars);
+        for (mono_s, c_s) in self.terms.iter() {
+            let mono = mono_s.iter().zip(mono).map(|(x, y)| x + y).collect();
+            p.add_term(mono, c_s * c, &m);
+        }
+        p
+    }
+
+    /// remainder of multivariate division by basis
+    pub fn reduce(&self, basis: &[Self]) -> Result<Self> {
+        let m = BigInt::from_str(MOD).unwrap();
+        let mut p = self.clone();
+        let mut r = Self::zero(self.nvars);
+        while let Some((lm, lc)) = p.leading_term() {
+            let (lm, lc) = (lm.clone(), lc.clone());
+            let divisor = basis.iter().find(|g| match g.leading_term() {
+                Some((glm, _)) => divides(glm, &lm),
+                None => false,
+            });
+            match divisor {
+                Some(g) => {
+                    let (glm, glc) = g.leading_term().unwrap();
+                    let mono: Monomial = lm.iter().zip(glm).map(|(x, y)| x - y).collect();
+                    let c = lc * inverse(glc.clone(), m.clone())?;
+                    p = &p - &g.mul_term(&mono, &c);
+                }
+                None => {
+                    p.terms.remove(&lm);
+                    r.terms.insert(lm, lc);
+                }
+            }
+        }
+        Ok(r)
+    }
+
+    /// roots in F_MOD of a polynomial in at most one variable.
+    /// errors for the zero polynomial and for more than one variable
+    pub fn roots(&self) -> Result<Vec<BigInt>> {
+        let vars = self.variables();
+        if vars.len() > 1 {
+            return Err(OctonionError::InvalidParameter(format!(
+                "{} is not univariate",
+                self
+            )));
+        }
+        if self.is_zero() {
+            return Err(OctonionError::InvalidParameter(
+                "every value is a root of 0".to_string(),
+            ));
+        }
+        let m = BigInt::from_str(MOD).unwrap();
+        let dense = match vars.first() {
+            Some(&var) => {
+                let degree = self.degree() as usize;
+                let mut dense = vec![BigInt::from(0); degree + 1];
+                for (mono, c) in self.terms.iter() {
+                    dense[mono[var] as usize] = c.clone();
+                }
+                dense
+            }
+            None => return Ok(Vec::new()),
+        };
+        let mut roots = dense::roots(&dense, &m)?;
+        roots.sort();
+        Ok(roots)
+    }
+}
+
+/// (lcm / lm(f)) f / lc(f) - (lcm / lm(g)) g / lc(g)
+pub fn s_polynomial<const MOD: &'static str>(
+    f: &Polynomial<MOD>,
+    g: &Polynomial<MOD>,
+) -> Result<Polynomial<MOD>> {
+    let m = BigInt::from_str(MOD).unwrap();
+    let (flm, flc) = match f.leading_term() {
+        Some(t) => t,
+        None => return Ok(Polynomial::zero(f.nvars)),
+    };
+    let (glm, glc) = match g.leading_term() {
+        Some(t) => t,
+        None => return Ok(Polynomial::zero(f.nvars)),
+    };
+    let l = lcm(flm, glm);
+    let fmono: Monomial = l.iter().zip(flm).map(|(x, y)| x - y).collect();
+    let gmono: Monomial = l.iter().zip(glm).map(|(x, y)| x - y).collect();
+    let finv = inverse(flc.clone(), m.clone())?;
+    let ginv = inverse(glc.clone(), m)?;
+    Ok(&f.mul_term(&fmono, &finv) - &g.mul_term(&gmono, &ginv))
+}
+
+/// reduced groebner basis in lex order, by buchberger's algorithm.
+/// [1] if the system has no solution in the algebraic closure.
+/// errors with NotInvertible if MOD is not prime
+pub fn groebner_basis<const MOD: &'static str>(
+    polys: &[Polynomial<MOD>],
+) -> Result<Vec<Polynomial<MOD>>> {
+    let mut basis: Vec<Polynomial<MOD>> = polys
+        .iter()
+        .filter(|p| !p.is_zero())
+        .map(|p| p.monic())
+        .collect::<Result<_>>()?;
+    let mut pairs: Vec<(usize, usize)> = (0..basis.len())
+        .flat_map(|j| (0..j).map(move |i| (i, j)))
+        .collect();
+
+    while let Some((i, j)) = pairs.pop() {
+        let (flm, _) = basis[i].leading_term().unwrap();
+        let (glm, _) = basis[j].leading_term().unwrap();
+        // coprime leading monomials reduce to 0
+        if flm.iter().zip(glm).all(|(x, y)| *x == 0 || *y == 0) {
+            continue;
+        }
+        let s = s_polynomial(&basis[i], &basis[j])?.reduce(&basis)?;
+        if s.is_zero() {
+            continue;
+        }
+        if s.is_constant() {
+            return Ok(vec![Polynomial::constant(s.nvars, BigInt::from(1))]);
+        }
+        basis.push(s.monic()?);
+        let k = basis.len() - 1;
+        pairs.extend((0..k).map(|i| (i, k)));
+    }
+
+    // minimal basis: drop every element whose leading monomial is divisible by another one
+    let mut minimal: Vec<Polynomial<MOD>> = Vec::new();
+    for (i, p) in basis.iter().enumerate() {
+        let (lm, _) = p.leading_term().unwrap();
+        let redundant = basis.iter().enumerate().any(|(j, q)| {
+            let (qlm, _) = q.leading_term().unwrap();
+            // of equal leading monomials keep the first
+            j != i && divides(qlm, lm) && (qlm != lm || j < i)
+        });
+        if !redundant {
+            minimal.push(p.clone());
+        }
+    }
+
+    // reduced basis: no term of an element is divisible by the others' leading monomials
+    let mut reduced = Vec::with_capacity(minimal.len());
+    for i in 0..minimal.len() {
+        let others: Vec<_> = minimal
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| *j != i)
+            .map(|(_, p)| p.clone())
+            .collect();
+        let (lm, _) = minimal[i].leading_term().unwrap();
+        let mut tail = minimal[i].clone();
+        tail.terms.remove(lm);
+        let mut p = tail.reduce(&others)?;
+        p.terms.insert(lm.clone(), BigInt::from(1));
+        reduced.push(p);
+    }
+    reduced.sort_by(|a, b| a.leading_term().unwrap().0.cmp(b.leading_term().unwrap().0));
+    Ok(reduced)
+}
+
+/// every solution in F_MOD of polys = 0 over the variables vars, in the order of vars.
+/// the other variables must not appear. errors if the system is not zero dimensional
+pub fn solve<const MOD: &'static str>(
+    polys: &[Polynomial<MOD>],
+    vars: &[usize],
+) -> Result<Vec<Vec<BigInt>>> {
+    if let Some(p) = polys
+        .iter()
+        .find(|p| p.variables().iter().any(|v| !vars.contains(v)))
+    {
+        return Err(OctonionError::InvalidParameter(format!(
+            "{} has variables other than {:?}",
+            p, vars
+        )));
+    }
+
+    // lex order eliminates the largest index first
+    let mut sorted = vars.to_vec();
+    sorted.sort();
+    sorted.dedup();
+    let solutions = solve_sorted(polys, &sorted)?;
+    let solutions = solutions
+        .into_iter()
+        .map(|solution| {
+            vars.iter()
+                .map(|v| solution[sorted.binary_search(v).unwrap()].clone())
+                .collect()
+        })
+        .collect();
+    Ok(solutions)
+}
+
+fn solve_sorted<const MOD: &'static str>(
+    polys: &[Polynomial<MOD>],
+    vars: &[usize],
+) -> Result<Vec<Vec<BigInt>>> {
+    let basis = groebner_basis(polys)?;
+    if basis.iter().any(|p| p.is_constant()) {
+        return Ok(Vec::new());
+    }
+    let (&last, rest) = match vars.split_last() {
+        Some(split) => split,
+        None => return Ok(vec![Vec::new()]),
+    };
+
+    // a zero dimensional basis has a polynomial in the smallest variable alone
+    let univariate = basis
+        .iter()
+        .find(|p| p.variables() == vec![last])
+        .ok_or_else(|| {
+            OctonionError::InvalidParameter(format!(
+                "x{} is not determined, the system is not zero dimensional",
+                last
+            ))
+        })?;
+
+    let mut solutions = Vec::new();
+    for root in univariate.roots()? {
+        let substituted: Vec<_> = basis.iter().map(|p| p.substitute(last, &root)).collect();
+        for mut solution in solve_sorted(&substituted, rest)? {
+            solution.push(root.clone());
+            solutions.push(solution);
+        }
+    }
+    Ok(solutions)
+}
+
+/// dense univariate polynomials, coefficients from the constant term up
+mod dense {
+    use num_bigint::BigInt;
+    use num_bigint::RandBigInt;
+
+    use super::reduce;
+    use crate::error::Result;
+    use crate::utils::inverse;
+
+    fn trim(mut a: Vec<BigInt>) -> Vec<BigInt> {
+        while a.last() == Some(&BigInt::from(0)) {
+            a.pop();
+        }
+        a
+    }
+
+    fn degree(a: &[BigInt]) -> usize {
+        a.len().saturating_sub(1)
+    }
+
+    fn sub(a: &[BigInt], b: &[BigInt], m: &BigInt) -> Vec<BigInt> {
+        let zero = BigInt::from(0);
+        let n = a.len().max(b.len());
+        let c = (0..n)
+            .map(|i| reduce(a.get(i).unwrap_or(&zero) - b.get(i).unwrap_or(&zero), m))
+            .collect();
+        trim(c)
+    }
+
+    fn mul(a: &[BigInt], b: &[BigInt], m: &BigInt) -> Vec<BigInt> {
+        if a.is_empty() || b.is_empty() {
+            return Vec::new();
+        }
+        let mut c = vec![BigInt::from(0); a.len() + b.len() - 1];
+        for (i, x) in a.iter().enumerate() {
+            for (j, y) in b.iter().enumerate() {
+                c[i + j] = (&c[i + j] + x * y) % m;
+            }
+        }
+        trim(c)
+    }
+
+    /// (a / b, a % b), b nonzero
+    fn div_rem(a: &[BigInt], b: &[BigInt], m: &BigInt) -> Result<(Vec<BigInt>, Vec<BigInt>)> {
+        let mut r = trim(a.to_vec());
+        if r.len() < b.len() {
+            return Ok((Vec::new(), r));
+        }
+        let inv = inverse(b.last().unwrap().clone(), m.clone())?;
+        let mut q = vec![BigInt::from(0); r.len() - b.len() + 1];
+        while r.len() >= b.len() {
+            let shift = r.len() - b.len();
+            let c = r.last().unwrap() * &inv % m;
+            for (i, y) in b.iter().enumerate() {
+                r[shift + i] = reduce(&r[shift + i] - &c * y, m);
+            }
+            q[shift] = c;
+            r = trim(r);
+        }
+        Ok((trim(q), r))
+    }
+
+    fn monic(a: Vec<BigInt>, m: &BigInt) -> Result<Vec<BigInt>> {
+        match a.last() {
+            Some(c) => {
+                let inv = inverse(c.clone(), m.clone())?;
+                Ok(a.iter().map(|x| x * &inv % m).collect())
+            }
+            None => Ok(a),
+        }
+    }
+
+    fn gcd(a: &[BigInt], b: &[BigInt], m: &BigInt) -> Result<Vec<BigInt>> {
+        let mut a = trim(a.to_vec());
+        let mut b = trim(b.to_vec());
+        while !b.is_empty() {
+            let (_, r) = div_rem(&a, &b, m)?;
+            a = b;
+            b = r;
+        }
+        monic(a, m)
+    }
+
+    /// base^e mod f
+    fn pow_mod(base: &[BigInt], e: &BigInt, f: &[BigInt], m: &BigInt) -> Result<Vec<BigInt>> {
+        let mut e = e.clone();
+        let mut base = div_rem(base, f, m)?.1;
+        let mut ans = div_rem(&[BigInt::from(1)], f, m)?.1;
+        while e != BigInt::from(0) {
+            if &e % 2 == BigInt::from(1) {
+                ans = div_rem(&mul(&ans, &base, m), f, m)?.1;
+            }
+            base = div_rem(&mul(&base, &base, m), f, m)?.1;
+            e /= 2;
+        }
+        Ok(ans)
+    }
+
+    /// distinct roots of a nonzero f
+    pub(super) fn roots(f: &[BigInt], m: &BigInt) -> Result<Vec<BigInt>> {
+        let f = monic(trim(f.to_vec()), m)?;
+        if degree(&f) == 0 {
+            return Ok(Vec::new());
+        }
+        // product of (x - r) over the roots r of f
+        let x = vec![BigInt::from(0), BigInt::from(1)];
+        let xp = pow_mod(&x, m, &f, m)?;
+        let split = gcd(&f, &sub(&xp, &x, m), m)?;
+        let mut roots = Vec::with_capacity(degree(&split));
+        split_roots(split, m, &mut roots)?;
+        Ok(roots)
+    }
+
+    /// cantor-zassenhaus for a monic f with distinct roots, all in F_m
+    fn split_roots(f: Vec<BigInt>, m: &BigInt, roots: &mut Vec<BigInt>) -> Result<()> {
+        match degree(&f) {
+            0 => return Ok(()),
+            1 => {
+                roots.push(reduce(-&f[0], m));
+                return Ok(());
+            }
+            _ => {}
+        }
+        let mut rng = rand::thread_rng();
+        let e: BigInt = (m - 1) / 2;
+        loop {
+            // gcd(f, (x + a)^((m - 1) / 2) - 1) holds the roots r with r + a a nonzero square
+            let a = rng.gen_bigint_range(&BigInt::from(0), m);
+            let h = pow_mod(&[a, BigInt::from(1)], &e, &f, m)?;
+            let d = gcd(&f, &sub(&h, &[BigInt::from(1)], m), m)?;
+            if degree(&d) > 0 && degree(&d) < degree(&f) {
+                let (q, _) = div_rem(&f, &d, m)?;
+                split_roots(monic(q, m)?, m, roots)?;
+                return split_roots(d, m, roots);
+            }
+        }
+    }
+}
+
+impl<const MOD: &'static str> Add<&Polynomial<MOD>> for &Polynomial<MOD> {
+    type Output = Polynomial<MOD>;
+    fn add(self, rhs: &Polynomial<MOD>) -> Polynomial<MOD> {
+        assert_eq!(
+            self.nvars, rhs.nvars,
+            "can't add polynomials in {} and {} variables",
+            self.nvars, rhs.nvars
+        );
+        let m = BigInt::from_str(MOD).unwrap();
+        let mut p = self.clone();
+        for (mono, c) in rhs.terms.iter() {
+            p.add_term(mono.clone(), c.clone(), &m);
+        }
+        p
+    }
+}
+
+impl<const MOD: &'static str> Sub<&Polynomial<MOD>> for &Polynomial<MOD> {
+    type Output = Polynomial<MOD>;
+    fn sub(self, rhs: &Polynomial<MOD>) -> Polynomial<MOD> {
+        self + &(-rhs)
+    }
+}
+
+impl<const MOD: &'static str> Neg for &Polynomial<MOD> {
+    type Output = Polynomial<MOD>;
+    fn neg(self) -> Polynomial<MOD> {
+        self.scale(&BigInt::from(-1))
+    }
+}
+
+impl<const MOD: &'static str> Mul<&Polynomial<MOD>> for &Polynomial<MOD> {
+    type Output = Polynomial<MOD>;
+    fn mul(self, rhs: &Polynomial<MOD>) -> Polynomial<MOD> {
+        assert_eq!(
+            self.nvars, rhs.nvars,
+            "can't multiply polynomials in {} and {} variables",
+            self.nvars, rhs.nvars
+        );
+        let mut p = Polynomial::zero(self.nvars);
+        for (mono, c) in rhs.terms.iter() {
+            p = &p + &self.mul_term(mono, c);
+        }
+        p
+    }
+}
+
+impl<const MOD: &'static str> Display for Polynomial<MOD> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        for (n, (mono, c)) in self.terms.iter().rev().enumerate() {
+            if n > 0 {
+                write!(f, " + ")?;
+            }
+            write!(f, "{}", c)?;
+            for (i, e) in mono.iter().enumerate() {
+                match e {
+                    0 => {}
+                    1 => write!(f, "*x{}", i)?,
+                    e => write!(f, "*x{}^{}", i, e)?,
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use num_bigint::RandBigInt;
+use quickcheck_macros::quickcheck;
+
+use super::*;
+use crate::consts::M521_STR;
+
+const M: &str = M521_STR;
+
+fn random_element() -> BigInt {
+    let m = BigInt::from_str(M).unwrap();
+    rand::thread_rng().gen_bigint_range(&BigInt::from(0), &m)
+}
+
+fn x(nvars: usize, i: usize) -> Polynomial<M> {
+    Polynomial::var(nvars, i)
+}
+
+fn c(nvars: usize, value: BigInt) -> Polynomial<M> {
+    Polynomial::constant(nvars, value)
+}
+
+#[quickcheck]
+fn test_ring_laws(a: i64, b: i64) -> bool {
+    let p = &(&x(2, 0) * &x(2, 1)) + &c(2, BigInt::from(a));
+    let q = &x(2, 0).pow(3) - &c(2, BigInt::from(b));
+    let r = &x(2, 1) + &x(2, 0);
+    &p * &(&q + &r) == &(&p * &q) + &(&p * &r)
+        && &p * &q == &q * &p
+        && (&p - &p).is_zero()
+        && (&p - &q) == -&(&q - &p)
+}
+
+#[quickcheck]
+fn test_eval_substitute(a: i64, b: i64) -> bool {
+    let (a, b) = (BigInt::from(a), BigInt::from(b));
+    let p = &(&x(2, 0).pow(2) * &x(2, 1)) - &(&x(2, 1) + &c(2, BigInt::from(7)));
+    let substituted = p.substitute(0, &a).substitute(1, &b);
+    substituted.is_constant() && substituted.eval(&[a.clone(), b.clone()]) == p.eval(&[a, b])
+}
+
+#[test]
+fn test_from_terms() {
+    assert!(Polynomial::<M>::from_terms(2, vec![(vec![1], BigInt::from(1))]).is_err());
+    let p = Polynomial::<M>::from_terms(
+        2,
+        vec![
+            (vec![1, 0], BigInt::from(2)),
+            (vec![1, 0], BigInt::from(-2)),
+            (vec![0, 3], BigInt::from(1)),
+        ],
+    )
+    .unwrap();
+    assert_eq!(p, x(2, 1).pow(3));
+    assert_eq!(p.variables(), vec![1]);
+    assert_eq!(p.degree(), 3);
+    assert_eq!(format!("{}", p), "1*x1^3");
+}
+
+#[test]
+fn test_roots() {
+    let m = BigInt::from_str(M).unwrap();
+    let rs: Vec<BigInt> = (0..3).map(|_| random_element()).collect();
+    // x^2 - 3 has no roots, 3 is not a square mod 2^521 - 1
+    let mut p = &x(1, 0).pow(2) - &c(1, BigInt::from(3));
+    for r in rs.iter() {
+        p = &p * &(&x(1, 0) - &c(1, r.clone()));
+    }
+    let mut expected = rs.clone();
+    expected.sort();
+    expected.dedup();
+    assert_eq!(p.roots().unwrap(), expected);
+
+    assert_eq!(c(1, BigInt::from(5)).roots().unwrap(), Vec::<BigInt>::new());
+    assert!(Polynomial::<M>::zero(1).roots().is_err());
+    assert!((&x(2, 0) * &x(2, 1)).roots().is_err());
+    assert_eq!(
+        (&x(1, 0) + &c(1, BigInt::from(1))).roots().unwrap(),
+        vec![&m - 1]
+    );
+}
+
+#[test]
+fn test_groebner_basis() {
+    // x0^2 + x1^2 - 1, x0 - x1
+    let f = &(&x(2, 0).pow(2) + &x(2, 1).pow(2)) - &c(2, BigInt::from(1));
+    let g = &x(2, 0) - &x(2, 1);
+    let basis = groebner_basis(&[f.clone(), g.clone()]).unwrap();
+    assert!(f.reduce(&basis).unwrap().is_zero());
+    assert!(g.reduce(&basis).unwrap().is_zero());
+    // x0 - x1 and x1^2 - 1/2
+    assert_eq!(basis.len(), 2);
+    assert_eq!(basis[0].variables(), vec![1]);
+    assert_eq!(basis[1], g);
+
+    let inconsistent = groebner_basis(&[x(2, 0), &x(2, 0) - &c(2, BigInt::from(1))]).unwrap();
+    assert_eq!(inconsistent, vec![c(2, BigInt::from(1))]);
+}
+
+#[test]
+fn test_composite_modulus() {
+    // 3 has no inverse mod 9
+    let three = Polynomial::<"9">::var(1, 0).scale(&BigInt::from(3));
+    assert!(matches!(
+        three.monic(),
+        Err(OctonionError::NotInvertible { .. })
+    ));
+    assert!(matches!(
+        groebner_basis(std::slice::from_ref(&three)),
+        Err(OctonionError::NotInvertible { .. })
+    ));
+    assert!(matches!(
+        three.roots(),
+        Err(OctonionError::NotInvertible { .. })
+    ));
+}
+
+#[test]
+fn test_solve() {
+    let (a, b, d) = (random_element(), random_element(), random_element());
+    // x0 + x1 = a + b, x0 x1 = a b, x2 = d x0
+    let system = vec![
+        &(&x(3, 0) + &x(3, 1)) - &c(3, &a + &b),
+        &(&x(3, 0) * &x(3, 1)) - &c(3, &a * &b),
+        &x(3, 2) - &(&x(3, 0) * &c(3, d.clone())),
+    ];
+    let m = BigInt::from_str(M).unwrap();
+    let mut solutions = solve(&system, &[2, 0, 1]).unwrap();
+    solutions.sort();
+    let mut expected = vec![
+        vec![&d * &a % &m, a.clone(), b.clone()],
+        vec![&d * &b % &m, b.clone(), a.clone()],
+    ];
+    expected.sort();
+    assert_eq!(solutions, expected);
+
+    // x0 x1 = 1 has a solution for every x1 != 0
+    assert!(solve(&[&(&x(2, 0) * &x(2, 1)) - &c(2, BigInt::from(1))], &[0, 1]).is_err());
+    assert!(solve(&[x(2, 0)], &[1]).is_err());
+    assert_eq!(
+        solve(&[x(2, 0), &x(2, 0) - &c(2, BigInt::from(1))], &[0, 1]).unwrap(),
+        Vec::<Vec<BigInt>>::new()
+    );
+}
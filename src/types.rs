@@ -8,6 +8,7 @@ use std::{fmt::Formatter, str::FromStr};
 use num_bigint;
 use num_bigint::BigInt;
 
+use crate::error::{OctonionError, Result};
 use crate::utils::inverse;
 
 #[cfg(test)]
@@ -146,7 +147,20 @@ impl<const MOD: &'static str> Octonion<MOD> {
             return None;
         }
         let norm2 = self.norm2();
-        return Some(&inverse(norm2, m) * self.conjugate());
+        let inv = inverse(norm2, m).ok()?;
+        Some(&inv * self.conjugate())
+    }
+
+    /// self * rhs^-1
+    pub fn checked_div(&self, rhs: &Self) -> Result<Self> {
+        let m = BigInt::from_str(MOD).unwrap();
+        match rhs.inverse() {
+            Some(rhs_inv) => Ok(self.clone() * rhs_inv),
+            None => Err(OctonionError::NotInvertible {
+                value: rhs.norm2(),
+                modulus: m,
+            }),
+        }
     }
 
     pub fn conjugate(&self) -> Self {
@@ -414,65 +428,27 @@ impl<const MOD: &'static str> Mul<Octonion<MOD>> for &BigInt {
     }
 }
 
+/// # Panics
+/// if rhs has no inverse, use checked_div to handle it
 impl<const MOD: &'static str> Div for Octonion<MOD> {
     type Output = Self;
 
     fn div(self, rhs: Self) -> Self::Output {
-        let m = BigInt::from_str(MOD).unwrap();
-        let divider = (self.a0.pow(2)
-            + self.a1.pow(2)
-            + self.a2.pow(2)
-            + self.a3.pow(2)
-            + self.a4.pow(2)
-            + self.a5.pow(2)
-            + self.a6.pow(2)
-            + self.a7.pow(2))
-            % &m;
-        if divider == BigInt::from(0) {
-            panic!("{:?} is zero, can't div.", self);
-        }
-
-        let rhs_inv = Self {
-            a0: (rhs.a0 * inverse(divider.clone(), m.clone())) % &m,
-            a1: ((&m - rhs.a1) * inverse(divider.clone(), m.clone())) % &m,
-            a2: ((&m - rhs.a2) * inverse(divider.clone(), m.clone())) % &m,
-            a3: ((&m - rhs.a3) * inverse(divider.clone(), m.clone())) % &m,
-            a4: ((&m - rhs.a4) * inverse(divider.clone(), m.clone())) % &m,
-            a5: ((&m - rhs.a5) * inverse(divider.clone(), m.clone())) % &m,
-            a6: ((&m - rhs.a6) * inverse(divider.clone(), m.clone())) % &m,
-            a7: ((&m - rhs.a7) * inverse(divider.clone(), m.clone())) % &m,
-        };
-        self * rhs_inv
+        match self.checked_div(&rhs) {
+            Ok(ans) => ans,
+            Err(_) => panic!("{:?} has no inverse, can't div.", rhs),
+        }
     }
 }
 
+/// # Panics
+/// if rhs has no inverse, use checked_div to handle it
 impl<const MOD: &'static str> DivAssign for Octonion<MOD> {
     fn div_assign(&mut self, rhs: Self) {
-        let m = BigInt::from_str(MOD).unwrap();
-        let divider = (self.a0.pow(2)
-            + self.a1.pow(2)
-            + self.a2.pow(2)
-            + self.a3.pow(2)
-            + self.a4.pow(2)
-            + self.a5.pow(2)
-            + self.a6.pow(2)
-            + self.a7.pow(2))
-            % &m;
-        if divider == BigInt::from(0) {
-            panic!("{:?} is zero, can't div.", self);
-        }
-
-        let rhs_inv = Self {
-            a0: (rhs.a0 * inverse(divider.clone(), m.clone())) % &m,
-            a1: (rhs.a1 * inverse(divider.clone(), m.clone())) % &m,
-            a2: (rhs.a2 * inverse(divider.clone(), m.clone())) % &m,
-            a3: (rhs.a3 * inverse(divider.clone(), m.clone())) % &m,
-            a4: (rhs.a4 * inverse(divider.clone(), m.clone())) % &m,
-            a5: (rhs.a5 * inverse(divider.clone(), m.clone())) % &m,
-            a6: (rhs.a6 * inverse(divider.clone(), m.clone())) % &m,
-            a7: (rhs.a7 * inverse(divider.clone(), m.clone())) % &m,
-        };
-        *self *= rhs_inv;
+        match self.checked_div(&rhs) {
+            Ok(ans) => *self = ans,
+            Err(_) => panic!("{:?} has no inverse, can't div.", rhs),
+        }
     }
 }
 
@@ -136,3 +136,17 @@ fn test_mul_div(a: Octonion<M>, b: Octonion<M>) -> bool {
     }
     return left == right;
 }
+
+/// (a b^-1) b = a
+#[quickcheck]
+fn test_checked_div(a: Octonion<M>, b: Octonion<M>) -> bool {
+    match a.checked_div(&b) {
+        Ok(q) => b.has_inv() && q * b == a,
+        Err(_) => !b.has_inv(),
+    }
+}
+
+#[test]
+fn test_checked_div_zero() {
+    assert!(Octonion::<M>::one().checked_div(&Octonion::zero()).is_err());
+}
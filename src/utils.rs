@@ -1,13 +1,14 @@
 use std::str::FromStr;
 
-use anyhow::Result;
 use num_bigint::BigInt;
 use num_bigint::RandBigInt;
+use rand::Rng;
 
+use crate::error::{OctonionError, Result};
 use crate::types::Octonion;
 
 /// find num, num^-1 mod m
-pub fn inverse(num: BigInt, m: BigInt) -> BigInt {
+pub fn inverse(num: BigInt, m: BigInt) -> Result<BigInt> {
     let mut x0 = BigInt::from(1);
     let mut y0 = BigInt::from(0);
     let mut x1 = BigInt::from(0);
@@ -33,11 +34,10 @@ pub fn inverse(num: BigInt, m: BigInt) -> BigInt {
         y1 = pre_y0 - q * pre_y1;
     }
     if a != BigInt::from(1) {
-        dbg!(a, b, x0, x1, y0, y1);
-        panic!(
-            "modular inverse does not exist for num: {:?}, moduler: {:?}",
-            num, m
-        );
+        return Err(OctonionError::NotInvertible {
+            value: num,
+            modulus: m,
+        });
     }
 
     if x0 < BigInt::from(0) {
@@ -45,21 +45,30 @@ pub fn inverse(num: BigInt, m: BigInt) -> BigInt {
         x0 -= (q - 1) * m.clone();
     }
 
-    return x0 % m;
+    Ok(x0 % m)
 }
 
 /// find r, r^2 === n mod p
 pub fn sqrt_with_mod(n: BigInt, p: BigInt) -> Result<BigInt> {
     if p < BigInt::from(3) {
-        return Err(anyhow::anyhow!("moduler p {} must be larger than 3", p));
+        return Err(OctonionError::InvalidModulus {
+            modulus: p,
+            reason: "must be larger than 3".to_string(),
+        });
     }
 
     if &p % 2 == BigInt::from(0) {
-        return Err(anyhow::anyhow!("moduler p {} must be odd! maybe...", p));
+        return Err(OctonionError::InvalidModulus {
+            modulus: p,
+            reason: "must be odd".to_string(),
+        });
     }
 
     if !is_residue(&n, &p) {
-        return Err(anyhow::anyhow!("n {} is not residue mod p {}", n, p));
+        return Err(OctonionError::NotResidue {
+            value: n,
+            modulus: p,
+        });
     }
 
     if p.clone() % BigInt::from(4) == BigInt::from(3) {
@@ -95,17 +104,13 @@ pub fn sqrt_with_mod(n: BigInt, p: BigInt) -> Result<BigInt> {
         }
 
         // find least i, 0 < i < m
-        let mut i = BigInt::from(1);
         // p-1 = q*2^s
         // m less than s which is initial value, then satisfy
         // 2^(m-1) < p
-        if t.modpow(&BigInt::from(2).modpow(&(&m - 1), &p), &p) == BigInt::from(1) {
-            i = &m - 1;
-        } else {
-            while t.modpow(&BigInt::from(2).modpow(&i, &p), &p) != BigInt::from(1) {
-                i += 1;
-                debug_assert!(i < m);
-            }
+        let mut i = BigInt::from(1);
+        while t.modpow(&BigInt::from(2).modpow(&i, &p), &p) != BigInt::from(1) {
+            i += 1;
+            debug_assert!(i < m);
         }
 
         let b = c.modpow(&BigInt::from(2).modpow(&(&m - &i - 1), &p), &p);
@@ -125,15 +130,478 @@ pub fn sqrt_with_mod(n: BigInt, p: BigInt) -> Result<BigInt> {
     }
 }
 
-/// is there sqrt(x)?
+/// is there sqrt(x)? p must be an odd prime
 pub fn is_residue(x: &BigInt, p: &BigInt) -> bool {
-    x.modpow(&((p - BigInt::from(1)) / BigInt::from(2)), &p) == BigInt::from(1)
+    legendre(x, p) == Ok(1)
+}
+
+/// x mod m in [0, m)
+fn reduce(x: &BigInt, m: &BigInt) -> BigInt {
+    let r = x % m;
+    if r < BigInt::from(0) {
+        r + m
+    } else {
+        r
+    }
+}
+
+fn check_odd(n: &BigInt) -> Result<()> {
+    if n < &BigInt::from(3) || n % 2 == BigInt::from(0) {
+        return Err(OctonionError::InvalidModulus {
+            modulus: n.clone(),
+            reason: "must be odd and larger than 1".to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// jacobi symbol (a / n) for odd n > 1, computed without modexp
+pub fn jacobi(a: &BigInt, n: &BigInt) -> Result<i8> {
+    check_odd(n)?;
+    let zero = BigInt::from(0);
+    let mut a = reduce(a, n);
+    let mut n = n.clone();
+    let mut t = 1;
+    while a != zero {
+        while &a % 2 == zero {
+            a /= 2;
+            let r = &n % 8;
+            if r == BigInt::from(3) || r == BigInt::from(5) {
+                t = -t;
+            }
+        }
+        std::mem::swap(&mut a, &mut n);
+        if &a % 4 == BigInt::from(3) && &n % 4 == BigInt::from(3) {
+            t = -t;
+        }
+        a %= &n;
+    }
+    if n == BigInt::from(1) {
+        Ok(t)
+    } else {
+        Ok(0)
+    }
+}
+
+/// legendre symbol (a / p), 1 for residues, -1 for non residues and 0 for multiples of p.
+/// p must be an odd prime, which is not checked
+pub fn legendre(a: &BigInt, p: &BigInt) -> Result<i8> {
+    jacobi(a, p)
+}
+
+/// find r, r^2 === n mod p by cipolla's algorithm.
+/// unlike sqrt_with_mod it doesn't depend on the power of 2 in p - 1
+pub fn sqrt_cipolla(n: &BigInt, p: &BigInt) -> Result<BigInt> {
+    check_odd(p)?;
+    let n = reduce(n, p);
+    if n == BigInt::from(0) {
+        return Ok(n);
+    }
+    if legendre(&n, p)? != 1 {
+        return Err(OctonionError::NotResidue {
+            value: n,
+            modulus: p.clone(),
+        });
+    }
+
+    // a with w = a^2 - n non residue, then sqrt(n) = (a + sqrt(w))^((p + 1) / 2) in F_p^2
+    let mut a = BigInt::from(1);
+    let w = loop {
+        let w = reduce(&(&a * &a - &n), p);
+        if legendre(&w, p)? == -1 {
+            break w;
+        }
+        a += 1;
+    };
+    let mul = |x: &(BigInt, BigInt), y: &(BigInt, BigInt)| {
+        (
+            (&x.0 * &y.0 + &x.1 * &y.1 % p * &w) % p,
+            (&x.0 * &y.1 + &x.1 * &y.0) % p,
+        )
+    };
+
+    let mut e: BigInt = (p + 1) / 2;
+    let mut base = (a, BigInt::from(1));
+    let mut r = (BigInt::from(1), BigInt::from(0));
+    while e != BigInt::from(0) {
+        if &e % 2 == BigInt::from(1) {
+            r = mul(&r, &base);
+        }
+        base = mul(&base, &base);
+        e /= 2;
+    }
+    debug_assert_eq!(r.1, BigInt::from(0));
+    Ok(r.0)
+}
+
+/// chinese remainder theorem.
+/// find x in [0, M), x === residues[i] mod moduli[i] where M is the product of moduli.
+/// moduli must be positive and pairwise coprime. returns (x, M)
+pub fn crt(residues: &[BigInt], moduli: &[BigInt]) -> Result<(BigInt, BigInt)> {
+    if residues.len() != moduli.len() {
+        return Err(OctonionError::DimensionMismatch(format!(
+            "{} residues for {} moduli",
+            residues.len(),
+            moduli.len()
+        )));
+    }
+    let mut x = BigInt::from(0);
+    let mut m = BigInt::from(1);
+    for (r, mi) in residues.iter().zip(moduli) {
+        if mi < &BigInt::from(1) {
+            return Err(OctonionError::InvalidModulus {
+                modulus: mi.clone(),
+                reason: "must be positive".to_string(),
+            });
+        }
+        // x + m k === r mod mi
+        let k = reduce(&((r - &x) * inverse(&m % mi, mi.clone())?), mi);
+        x += &m * k;
+        m *= mi;
+    }
+    Ok((x, m))
+}
+
+/// find x, x^n === a mod p for an odd prime p.
+/// a has an n-th root iff a^((p - 1) / gcd(n, p - 1)) === 1.
+/// finding a digit of a discrete log is linear in the prime factors of n,
+/// so n should be small or smooth
+pub fn nth_root_mod(a: &BigInt, n: u64, p: &BigInt) -> Result<BigInt> {
+    check_odd(p)?;
+    if n == 0 {
+        return Err(OctonionError::InvalidParameter(
+            "0th root is not defined".to_string(),
+        ));
+    }
+    let a = reduce(a, p);
+    if a == BigInt::from(0) {
+        return Ok(a);
+    }
+    let p1: BigInt = p - 1;
+    let n = BigInt::from(n);
+    let d = gcd(&n, &p1);
+    if a.modpow(&(&p1 / &d), p) != BigInt::from(1) {
+        return Err(OctonionError::NotResidue {
+            value: a,
+            modulus: p.clone(),
+        });
+    }
+
+    // e n === d mod p - 1, so x = y^e for y^d = a
+    let e = inverse(&n / &d, &p1 / &d)?;
+    let y = root_of_divisor(&a, &d, p);
+    let x = y.modpow(&e, p);
+    debug_assert_eq!(x.modpow(&n, p), a);
+    Ok(x)
+}
+
+/// find x, x^3 === a mod p
+pub fn cbrt_mod(a: &BigInt, p: &BigInt) -> Result<BigInt> {
+    nth_root_mod(a, 3, p)
+}
+
+/// y^d === a where d | p - 1 and a is a d-th power.
+/// p - 1 = m q where the primes of m divide d and gcd(q, d) = 1.
+/// y0 = a^(d^-1 mod q) is a root up to an error in the subgroup of order m,
+/// which is removed by a discrete log there (pohlig-hellman)
+fn root_of_divisor(a: &BigInt, d: &BigInt, p: &BigInt) -> BigInt {
+    let one = BigInt::from(1);
+    if d == &one {
+        return a.clone();
+    }
+    let p1: BigInt = p - 1;
+    let primes = prime_factors(d);
+    let mut q = p1.clone();
+    let mut m = BigInt::from(1);
+    for r in primes.iter() {
+        while &q % r == BigInt::from(0) {
+            q /= r;
+            m *= r;
+        }
+    }
+
+    let y0 = a.modpow(&inverse(d % &q, q.clone()).unwrap(), p);
+    // a = y0^d delta, delta in the subgroup of order m
+    let delta = a * inverse(y0.modpow(d, p), p.clone()).unwrap() % p;
+
+    // generator of the subgroup of order m
+    let mut c = BigInt::from(2);
+    while primes.iter().any(|r| c.modpow(&(&p1 / r), p) == one) {
+        c += 1;
+    }
+    let h = c.modpow(&q, p);
+
+    // h^(d i) = delta
+    let j = discrete_log(&delta, &h, &m, &primes, p);
+    let g = gcd(d, &m);
+    debug_assert_eq!(&j % &g, BigInt::from(0));
+    let i = (&j / &g) * inverse(d / &g % (&m / &g), &m / &g).unwrap();
+    y0 * h.modpow(&i, p) % p
+}
+
+/// k with h^k === x, where h has order m and every prime of m is in primes
+fn discrete_log(x: &BigInt, h: &BigInt, m: &BigInt, primes: &[BigInt], p: &BigInt) -> BigInt {
+    let one = BigInt::from(1);
+    let mut residues = Vec::with_capacity(primes.len());
+    let mut moduli = Vec::with_capacity(primes.len());
+    for r in primes {
+        // r^e || m
+        let mut re = BigInt::from(1);
+        let mut e = 0;
+        while m % (&re * r) == BigInt::from(0) {
+            re *= r;
+            e += 1;
+        }
+        let hr = h.modpow(&(m / &re), p);
+        let xr = x.modpow(&(m / &re), p);
+        // order r
+        let gamma = hr.modpow(&(&re / r), p);
+
+        // k = k_0 + k_1 r + ... , one digit at a time
+        let mut k = BigInt::from(0);
+        let mut rk = BigInt::from(1);
+        for _ in 0..e {
+            let t = (hr.modpow(&(&re - &k), p) * &xr % p).modpow(&(&re / (&rk * r)), p);
+            let mut digit = BigInt::from(0);
+            let mut power = one.clone();
+            while power != t {
+                power = power * &gamma % p;
+                digit += 1;
+                debug_assert!(&digit < r);
+            }
+            k += digit * &rk;
+            rk *= r;
+        }
+        residues.push(k);
+        moduli.push(re);
+    }
+    crt(&residues, &moduli).unwrap().0
+}
+
+/// distinct prime factors by trial division, n is small here
+fn prime_factors(n: &BigInt) -> Vec<BigInt> {
+    let mut n = n.clone();
+    let mut factors = Vec::new();
+    let mut r = BigInt::from(2);
+    while &r * &r <= n {
+        if &n % &r == BigInt::from(0) {
+            while &n % &r == BigInt::from(0) {
+                n /= &r;
+            }
+            factors.push(r.clone());
+        }
+        r += 1;
+    }
+    if n > BigInt::from(1) {
+        factors.push(n);
+    }
+    factors
+}
+
+/// first 13 primes, used for trial division and as Miller-Rabin bases
+const SMALL_PRIMES: [u32; 13] = [2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41];
+
+/// random Miller-Rabin bases tried by is_prime on top of SMALL_PRIMES
+pub const MILLER_RABIN_ROUNDS: usize = 8;
+
+/// Miller-Rabin with SMALL_PRIMES and `rounds` random bases.
+/// deterministic for n < 3.3 * 10^24, otherwise a composite passes with probability < 4^-rounds.
+pub fn is_probable_prime(n: &BigInt, rounds: usize) -> bool {
+    let zero = BigInt::from(0);
+    let one = BigInt::from(1);
+    let two = BigInt::from(2);
+    if n < &two {
+        return false;
+    }
+    for &q in SMALL_PRIMES.iter() {
+        let q = BigInt::from(q);
+        if n == &q {
+            return true;
+        }
+        if n % &q == zero {
+            return false;
+        }
+    }
+
+    // n - 1 = d * 2^s
+    let n_minus_one = n - &one;
+    let mut d = n_minus_one.clone();
+    let mut s = 0;
+    while &d % 2 == zero {
+        d /= 2;
+        s += 1;
+    }
+
+    let is_witness = |a: &BigInt| {
+        let mut x = a.modpow(&d, n);
+        if x == one || x == n_minus_one {
+            return false;
+        }
+        for _ in 1..s {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                return false;
+            }
+        }
+        true
+    };
+
+    if SMALL_PRIMES.iter().any(|&a| is_witness(&BigInt::from(a))) {
+        return false;
+    }
+    let mut rng = rand::thread_rng();
+    for _ in 0..rounds {
+        let a = rng.gen_bigint_range(&two, &n_minus_one);
+        if is_witness(&a) {
+            return false;
+        }
+    }
+    true
+}
+
+/// is_probable_prime with MILLER_RABIN_ROUNDS
+pub fn is_prime(n: &BigInt) -> bool {
+    is_probable_prime(n, MILLER_RABIN_ROUNDS)
+}
+
+/// check p can be used as MOD.
+///
+/// sqrt_with_mod, is_residue and inverse need an odd prime field, and find_g_h needs 1/2.
+/// that is also enough for find_g_h: for every odd prime its slice of the (g, h) counted by
+/// analysis::exhaustive::count_g_h is non empty, and a random try lands in it with probability
+/// above 1/16, about 0.066 for p = 5, the worst case, and close to 1/4 for large p.
+/// so every other modulus is rejected here and every accepted one has a schema
+pub fn check_modulus(p: &BigInt) -> Result<()> {
+    let invalid = |reason: &str| OctonionError::InvalidModulus {
+        modulus: p.clone(),
+        reason: reason.to_string(),
+    };
+    if p < &BigInt::from(3) {
+        return Err(invalid("must be at least 3"));
+    }
+    if p % 2 == BigInt::from(0) {
+        return Err(invalid("must be odd"));
+    }
+    if !is_prime(p) {
+        return Err(invalid("must be prime"));
+    }
+    Ok(())
+}
+
+/// give up after 64 * bits^2 candidates, far more than a safe prime needs on average
+fn max_prime_attempts(bits: u64) -> u64 {
+    64 * bits * bits
+}
+
+/// random prime in [2^(bits - 1), 2^bits)
+pub fn gen_prime<R: Rng + ?Sized>(bits: u64, rng: &mut R) -> Result<BigInt> {
+    gen_prime_congruent(bits, &BigInt::from(0), &BigInt::from(1), rng)
+}
+
+/// random prime p in [2^(bits - 1), 2^bits) with p = residue mod modulus,
+/// e.g. residue 3 and modulus 4 for a p where sqrt_with_mod takes the fast path
+pub fn gen_prime_congruent<R: Rng + ?Sized>(
+    bits: u64,
+    residue: &BigInt,
+    modulus: &BigInt,
+    rng: &mut R,
+) -> Result<BigInt> {
+    if bits < 2 {
+        return Err(OctonionError::InvalidParameter(format!(
+            "a prime needs at least 2 bits, got {}",
+            bits
+        )));
+    }
+    if modulus < &BigInt::from(1) {
+        return Err(OctonionError::InvalidParameter(format!(
+            "modulus {} must be positive",
+            modulus
+        )));
+    }
+    let residue = {
+        let mut r = residue % modulus;
+        if r < BigInt::from(0) {
+            r += modulus;
+        }
+        r
+    };
+    if modulus > &BigInt::from(2) && gcd(&residue, modulus) != BigInt::from(1) {
+        return Err(OctonionError::InvalidParameter(format!(
+            "residue {} and modulus {} must be coprime",
+            residue, modulus
+        )));
+    }
+
+    let low = BigInt::from(1) << (bits - 1) as usize;
+    let high = BigInt::from(1) << bits as usize;
+    for _ in 0..max_prime_attempts(bits) {
+        let mut p = rng.gen_bigint_range(&low, &high);
+        p -= &p % modulus;
+        p += &residue;
+        if p < low {
+            p += modulus;
+        }
+        if p >= high {
+            continue;
+        }
+        if is_prime(&p) {
+            return Ok(p);
+        }
+    }
+    Err(OctonionError::InvalidParameter(format!(
+        "no {} bit prime = {} mod {} found",
+        bits, residue, modulus
+    )))
+}
+
+/// random safe prime p = 2q + 1 in [2^(bits - 1), 2^bits), q prime.
+/// p = 3 mod 4 whenever bits > 3. slow for large bits.
+pub fn gen_safe_prime<R: Rng + ?Sized>(bits: u64, rng: &mut R) -> Result<BigInt> {
+    if bits < 3 {
+        return Err(OctonionError::InvalidParameter(format!(
+            "a safe prime needs at least 3 bits, got {}",
+            bits
+        )));
+    }
+    let low = BigInt::from(1) << (bits - 2) as usize;
+    let high = BigInt::from(1) << (bits - 1) as usize;
+    for _ in 0..max_prime_attempts(bits) {
+        let q = rng.gen_bigint_range(&low, &high);
+        if !is_prime(&q) {
+            continue;
+        }
+        let p = 2 * &q + 1;
+        if is_prime(&p) {
+            return Ok(p);
+        }
+    }
+    Err(OctonionError::InvalidParameter(format!(
+        "no {} bit safe prime found",
+        bits
+    )))
+}
+
+fn gcd(a: &BigInt, b: &BigInt) -> BigInt {
+    let mut a = a.clone();
+    let mut b = b.clone();
+    while b != BigInt::from(0) {
+        let r = &a % &b;
+        a = b;
+        b = r;
+    }
+    a
 }
 
 pub fn gen_rand_octonion_which_has_inv<const MOD: &'static str>() -> Octonion<MOD> {
+    gen_rand_octonion_which_has_inv_with_rng(&mut rand::thread_rng())
+}
+
+/// gen_rand_octonion_which_has_inv drawing from rng, deterministic for a seeded rng
+pub fn gen_rand_octonion_which_has_inv_with_rng<const MOD: &'static str, R: Rng + ?Sized>(
+    rng: &mut R,
+) -> Octonion<MOD> {
     let m = BigInt::from_str(MOD).unwrap();
 
-    let mut rng = rand::thread_rng();
     let a0: BigInt = rng.gen_bigint_range(&BigInt::from(0), &m);
     let a1: BigInt = rng.gen_bigint_range(&BigInt::from(0), &m);
     let a2: BigInt = rng.gen_bigint_range(&BigInt::from(0), &m);
@@ -178,7 +646,19 @@ mod tests {
         if num == BigInt::from(0) {
             return true;
         }
-        (num.clone() * inverse(num.clone(), m.clone())) % m.clone() == BigInt::from(1)
+        (num.clone() * inverse(num.clone(), m.clone()).unwrap()) % m.clone() == BigInt::from(1)
+    }
+
+    #[test]
+    fn test_inverse_not_invertible() {
+        assert_eq!(
+            inverse(BigInt::from(6), BigInt::from(9)),
+            Err(OctonionError::NotInvertible {
+                value: BigInt::from(6),
+                modulus: BigInt::from(9),
+            })
+        );
+        assert!(inverse(BigInt::from(0), BigInt::from(7)).is_err());
     }
 
     // #[test]
@@ -221,6 +701,232 @@ mod tests {
     //     assert_eq!(r.pow(2) % &*M, a);
     // }
 
+    #[quickcheck]
+    fn test_jacobi(a: i32, n: u16) -> bool {
+        let n = (n as i64) | 1;
+        if n == 1 {
+            return true;
+        }
+        // product of legendre symbols by euler's criterion
+        let mut expected = 1;
+        let mut rest = n;
+        let mut q = 3;
+        while rest > 1 {
+            while rest % q == 0 {
+                let e = BigInt::from(a).modpow(&BigInt::from((q - 1) / 2), &BigInt::from(q));
+                let l = reduce(&BigInt::from(a), &BigInt::from(q));
+                expected *= if l == BigInt::from(0) {
+                    0
+                } else if e == BigInt::from(1) {
+                    1
+                } else {
+                    -1
+                };
+                rest /= q;
+            }
+            q += 2;
+        }
+        jacobi(&BigInt::from(a), &BigInt::from(n)) == Ok(expected)
+    }
+
+    #[quickcheck]
+    fn test_legendre(a: usize) -> bool {
+        let m = BigInt::from_str(M521_STR).unwrap();
+        let a = BigInt::from(a);
+        let euler = a.modpow(&((&m - 1) / 2), &m) == BigInt::from(1);
+        (legendre(&a, &m).unwrap() == 1) == euler
+            && legendre(&(&a * &a), &m).unwrap() == if a == BigInt::from(0) { 0 } else { 1 }
+    }
+
+    #[test]
+    fn test_jacobi_invalid() {
+        assert!(jacobi(&BigInt::from(3), &BigInt::from(8)).is_err());
+        assert!(jacobi(&BigInt::from(3), &BigInt::from(1)).is_err());
+        assert_eq!(jacobi(&BigInt::from(-1), &BigInt::from(7)), Ok(-1));
+        assert_eq!(jacobi(&BigInt::from(6), &BigInt::from(9)), Ok(0));
+    }
+
+    #[quickcheck]
+    fn test_sqrt_cipolla(a: usize) -> bool {
+        let m = BigInt::from_str(M521_STR).unwrap();
+        // 2^(p-1) is 65537 = 1 mod 2^16, so tonelli-shanks has work to do here too
+        let p = BigInt::from(65537);
+
+        [m, p].iter().all(|p| {
+            let square = BigInt::from(a) * BigInt::from(a) % p;
+            let r = sqrt_cipolla(&square, p).unwrap();
+            if square == BigInt::from(0) {
+                return r == square;
+            }
+            let r_ts = sqrt_with_mod(square.clone(), p.clone()).unwrap();
+            r.modpow(&BigInt::from(2), p) == square && (r == r_ts || r == p - r_ts)
+        })
+    }
+
+    #[test]
+    fn test_sqrt_cipolla_not_residue() {
+        assert_eq!(
+            sqrt_cipolla(&BigInt::from(3), &BigInt::from(7)),
+            Err(OctonionError::NotResidue {
+                value: BigInt::from(3),
+                modulus: BigInt::from(7),
+            })
+        );
+    }
+
+    #[quickcheck]
+    fn test_crt(x: u64) -> bool {
+        let moduli: Vec<BigInt> = [7, 11, 64, 9, 25, 13, 17]
+            .iter()
+            .map(|&m| BigInt::from(m))
+            .collect();
+        let x = BigInt::from(x);
+        let residues: Vec<BigInt> = moduli.iter().map(|m| &x % m).collect();
+        let (y, m) = crt(&residues, &moduli).unwrap();
+        m == BigInt::from(7u64 * 11 * 64 * 9 * 25 * 13 * 17) && y == x % m
+    }
+
+    #[test]
+    fn test_crt_invalid() {
+        let two = BigInt::from(2);
+        assert!(crt(std::slice::from_ref(&two), &[]).is_err());
+        assert!(crt(
+            &[two.clone(), two.clone()],
+            &[BigInt::from(6), BigInt::from(4)]
+        )
+        .is_err());
+        assert!(crt(std::slice::from_ref(&two), &[BigInt::from(0)]).is_err());
+        assert_eq!(crt(&[], &[]), Ok((BigInt::from(0), BigInt::from(1))));
+    }
+
+    #[quickcheck]
+    fn test_nth_root_mod(x: usize, n: u8) -> bool {
+        let n = n as u64 % 40 + 1;
+        // 7681 - 1 = 2^9 3 5, 2^31 - 2 = 2 3^2 7 11 31 151 331
+        let ps = [
+            BigInt::from(7681),
+            BigInt::from_str(crate::consts::M31_STR).unwrap(),
+            BigInt::from_str(M521_STR).unwrap(),
+        ];
+        ps.iter().all(|p| {
+            let a = BigInt::from(x).modpow(&BigInt::from(n), p);
+            let r = nth_root_mod(&a, n, p).unwrap();
+            r.modpow(&BigInt::from(n), p) == a
+        })
+    }
+
+    #[quickcheck]
+    fn test_cbrt_mod(x: usize) -> bool {
+        let p = BigInt::from_str(crate::consts::M31_STR).unwrap();
+        let a = BigInt::from(x).modpow(&BigInt::from(3), &p);
+        cbrt_mod(&a, &p).unwrap().modpow(&BigInt::from(3), &p) == a
+    }
+
+    #[test]
+    fn test_nth_root_mod_invalid() {
+        let seven = BigInt::from(7);
+        // cubes mod 7 are 0, 1 and 6
+        assert!(matches!(
+            cbrt_mod(&BigInt::from(2), &seven),
+            Err(OctonionError::NotResidue { .. })
+        ));
+        let r = cbrt_mod(&BigInt::from(6), &seven).unwrap();
+        assert_eq!(r.modpow(&BigInt::from(3), &seven), BigInt::from(6));
+        assert!(nth_root_mod(&BigInt::from(2), 0, &seven).is_err());
+        assert!(nth_root_mod(&BigInt::from(2), 2, &BigInt::from(8)).is_err());
+        // gcd(5, 6) = 1, every element has a unique 5th root
+        for a in 1..7 {
+            let r = nth_root_mod(&BigInt::from(a), 5, &seven).unwrap();
+            assert_eq!(r.modpow(&BigInt::from(5), &seven), BigInt::from(a));
+        }
+    }
+
+    #[quickcheck]
+    fn test_is_prime(n: u16) -> bool {
+        let n = n as u32;
+        let by_trial_division = n >= 2
+            && (2..n)
+                .take_while(|d| d * d <= n)
+                .all(|d| !n.is_multiple_of(d));
+        is_prime(&BigInt::from(n)) == by_trial_division
+    }
+
+    #[test]
+    fn test_is_prime_large() {
+        use crate::consts::{M2203_STR, M31_STR};
+
+        assert!(is_prime(&BigInt::from_str(M31_STR).unwrap()));
+        assert!(is_prime(&BigInt::from_str(M521_STR).unwrap()));
+        assert!(is_prime(&BigInt::from_str(M2203_STR).unwrap()));
+        // largest u16 prime
+        assert!(is_prime(&BigInt::from(65521)));
+        // carmichael numbers
+        assert!(!is_prime(&BigInt::from(561)));
+        assert!(!is_prime(&BigInt::from(41041)));
+        assert!(!is_prime(&BigInt::from_str("3825123056546413051").unwrap()));
+        // M31 * M521
+        let composite = BigInt::from_str(M31_STR).unwrap() * BigInt::from_str(M521_STR).unwrap();
+        assert!(!is_prime(&composite));
+        assert!(!is_prime(&(BigInt::from(2).pow(521) + 1)));
+    }
+
+    #[test]
+    fn test_check_modulus() {
+        assert!(check_modulus(&BigInt::from(3)).is_ok());
+        assert!(check_modulus(&BigInt::from(5)).is_ok());
+        assert!(check_modulus(&BigInt::from_str(M521_STR).unwrap()).is_ok());
+        for bad in [-7, 0, 1, 2, 4, 9, 15, 561].iter() {
+            assert!(matches!(
+                check_modulus(&BigInt::from(*bad)),
+                Err(OctonionError::InvalidModulus { .. })
+            ));
+        }
+    }
+
+    #[quickcheck]
+    fn test_gen_prime(bits: u8) -> bool {
+        let bits = (bits % 128 + 2) as u64;
+        let mut rng = rand::thread_rng();
+        let p = gen_prime(bits, &mut rng).unwrap();
+        p.bits() == bits && is_prime(&p)
+    }
+
+    #[quickcheck]
+    fn test_gen_prime_congruent(bits: u8) -> bool {
+        let bits = (bits % 128 + 4) as u64;
+        let mut rng = rand::thread_rng();
+        let four = BigInt::from(4);
+        let p1 = gen_prime_congruent(bits, &BigInt::from(1), &four, &mut rng).unwrap();
+        let p3 = gen_prime_congruent(bits, &BigInt::from(3), &four, &mut rng).unwrap();
+        p1.bits() == bits
+            && p3.bits() == bits
+            && &p1 % &four == BigInt::from(1)
+            && &p3 % &four == BigInt::from(3)
+            && is_prime(&p1)
+            && is_prime(&p3)
+    }
+
+    #[test]
+    fn test_gen_prime_invalid() {
+        let mut rng = rand::thread_rng();
+        let four = BigInt::from(4);
+        assert!(gen_prime(1, &mut rng).is_err());
+        assert!(gen_prime_congruent(16, &BigInt::from(2), &four, &mut rng).is_err());
+        assert!(gen_prime_congruent(16, &BigInt::from(1), &BigInt::from(0), &mut rng).is_err());
+        // [8, 16) has no prime = 1 mod 8
+        assert!(gen_prime_congruent(4, &BigInt::from(1), &BigInt::from(8), &mut rng).is_err());
+        assert_eq!(gen_prime(2, &mut rng).unwrap().bits(), 2);
+    }
+
+    #[quickcheck]
+    fn test_gen_safe_prime(bits: u8) -> bool {
+        let bits = (bits % 64 + 3) as u64;
+        let mut rng = rand::thread_rng();
+        let p = gen_safe_prime(bits, &mut rng).unwrap();
+        let q: BigInt = (&p - 1) / 2;
+        p.bits() == bits && is_prime(&p) && is_prime(&q)
+    }
+
     #[quickcheck]
     fn test_gen_rand_octonion_which_has_inv(_: usize) -> bool {
         gen_rand_octonion_which_has_inv::<M521_STR>().has_inv()